@@ -0,0 +1,80 @@
+// cargo bench --bench frame
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use macroquad::prelude as mq;
+
+use raycaster::map::Map;
+use raycaster::render::{self, Camera, Textures};
+use raycaster::{EYE_HEIGHT, FOV, RENDER_HEIGHT, RENDER_WIDTH, TILE_SIZE};
+
+fn camera(x: f32, y: f32, angle: f32, angle_vertical: f32) -> Camera {
+    Camera {
+        pos: mq::Vec2::new(x, y) * TILE_SIZE as f32,
+        angle: angle.to_radians(),
+        angle_vertical: angle_vertical.to_radians(),
+        fov: FOV,
+        height: EYE_HEIGHT,
+    }
+}
+
+// a full frame from the default level's spawn, on one thread and on all of them
+fn render_frame(c: &mut Criterion) {
+    let map = Map::parse(include_str!("../resources/maps/default.map")).unwrap();
+    let textures = Textures::load();
+    let camera = camera(4.5, 4.5, 30.0, 10.0);
+
+    let mut group = c.benchmark_group("render_frame");
+    for size in [128, 256, RENDER_WIDTH.min(RENDER_HEIGHT)] {
+        let mut thread_counts = vec![1, render::worker_threads()];
+        thread_counts.dedup();
+        for threads in thread_counts {
+            let mut image = mq::Image::gen_image_color(size as u16, size as u16, mq::BLACK);
+            let id = BenchmarkId::new(format!("{}_threads", threads), format!("{0}x{0}", size));
+            group.bench_function(id, |b| {
+                b.iter(|| {
+                    black_box(render::render_frame_threaded(
+                        &mut image, &map, &camera, &textures, size, threads,
+                    ))
+                })
+            });
+        }
+    }
+    group.finish();
+}
+
+const FLYTHROUGH_FRAMES: u32 = 120;
+
+// walks down the open column in the middle of the large level, looking around
+fn flythrough(c: &mut Criterion) {
+    let map = Map::parse(include_str!("../resources/maps/large.map")).unwrap();
+    let textures = Textures::load();
+    let cameras: Vec<Camera> = (0..FLYTHROUGH_FRAMES)
+        .map(|i| {
+            let t = i as f32 / FLYTHROUGH_FRAMES as f32;
+            let sway = (t * 8.0 * std::f32::consts::PI).sin();
+            camera(32.5, 2.5 + t * 123.0, 90.0 + 60.0 * sway, 10.0 * sway)
+        })
+        .collect();
+
+    let mut image =
+        mq::Image::gen_image_color(RENDER_WIDTH as u16, RENDER_HEIGHT as u16, mq::BLACK);
+    let mut group = c.benchmark_group("flythrough");
+    group.sample_size(10);
+    group.bench_function("large", |b| {
+        b.iter(|| {
+            for camera in &cameras {
+                black_box(render::render_frame(
+                    &mut image,
+                    &map,
+                    camera,
+                    &textures,
+                    RENDER_WIDTH,
+                ));
+            }
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, render_frame, flythrough);
+criterion_main!(benches);
@@ -0,0 +1,37 @@
+// cargo bench --bench ray
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use macroquad::prelude as mq;
+
+use raycaster::map::Map;
+use raycaster::ray::Ray;
+use raycaster::TILE_SIZE;
+
+// rays in every direction from the spawn, so short and long hits are mixed
+fn cast_ray(c: &mut Criterion) {
+    let maps = [
+        ("default", include_str!("../resources/maps/default.map")),
+        ("large", include_str!("../resources/maps/large.map")),
+    ];
+
+    let mut group = c.benchmark_group("cast_ray");
+    for (name, source) in maps {
+        let map = Map::parse(source).unwrap();
+        let pos = map.spawn * TILE_SIZE as f32;
+        let rays: Vec<Ray> = (0..360)
+            .map(|angle| Ray::new(pos, mq::Vec2::from_angle((angle as f32).to_radians())))
+            .collect();
+
+        group.bench_with_input(BenchmarkId::from_parameter(name), &map, |b, map| {
+            b.iter(|| {
+                for ray in &rays {
+                    black_box(ray.cast_ray(map));
+                }
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, cast_ray);
+criterion_main!(benches);
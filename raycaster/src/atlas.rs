@@ -0,0 +1,250 @@
+use macroquad::prelude as mq;
+
+use std::fmt;
+use std::path::Path;
+
+/*
+    Atlas descriptor format:
+
+    # comments start with a '#'
+    image: WolfensteinTextures.png  # relative to the descriptor
+    tile_size: 64 64                # width height (in pixels)
+    grid: 1 3                       # columns rows
+    textures:
+    eagle
+    greystone
+    mossy
+
+    Everything after `textures:` is a name per texture, in the order they are
+    laid out in the image (left to right, top to bottom).
+    Textures are numbered from 1 in the same order.
+*/
+
+#[derive(Debug)]
+pub enum AtlasError {
+    Io(std::io::Error),
+    Syntax { line: usize },
+    UnknownKey { line: usize, key: String },
+    InvalidValue { line: usize, key: String },
+    DuplicateName { line: usize, name: String },
+    Decode(String),
+    MissingImage,
+    MissingTileSize,
+    NoTextures,
+    TooManyTextures { capacity: u32 },
+    ImageTooSmall,
+}
+impl fmt::Display for AtlasError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AtlasError::Io(err) => write!(f, "could not read atlas: {}", err),
+            AtlasError::Syntax { line } => {
+                write!(f, "line {}: expected `key: value` or `textures:`", line)
+            }
+            AtlasError::UnknownKey { line, key } => {
+                write!(f, "line {}: unknown key `{}`", line, key)
+            }
+            AtlasError::InvalidValue { line, key } => {
+                write!(f, "line {}: invalid value for `{}`", line, key)
+            }
+            AtlasError::DuplicateName { line, name } => {
+                write!(f, "line {}: texture `{}` is already defined", line, name)
+            }
+            AtlasError::Decode(err) => write!(f, "could not decode image: {}", err),
+            AtlasError::MissingImage => write!(f, "atlas has no `image:`"),
+            AtlasError::MissingTileSize => write!(f, "atlas has no `tile_size:`"),
+            AtlasError::NoTextures => write!(f, "atlas has no `textures:`"),
+            AtlasError::TooManyTextures { capacity } => {
+                write!(
+                    f,
+                    "atlas has more textures than its grid fits ({})",
+                    capacity
+                )
+            }
+            AtlasError::ImageTooSmall => write!(f, "image is smaller than the grid"),
+        }
+    }
+}
+impl std::error::Error for AtlasError {}
+impl From<std::io::Error> for AtlasError {
+    fn from(err: std::io::Error) -> Self {
+        AtlasError::Io(err)
+    }
+}
+
+// the parts of a descriptor that don't depend on the image
+struct Descriptor {
+    image_path: String,
+    tile_width: u32,
+    tile_height: u32,
+    columns: u32,
+    rows: Option<u32>,
+    names: Vec<String>,
+}
+
+pub struct Atlas {
+    pub image: mq::Image,
+    pub tile_width: u32,
+    pub tile_height: u32,
+    pub columns: u32,
+    pub names: Vec<String>,
+}
+impl Atlas {
+    pub fn load(path: &str) -> Result<Self, AtlasError> {
+        let source = std::fs::read_to_string(path)?;
+        let descriptor = parse_descriptor(&source)?;
+
+        let image_path = Path::new(path)
+            .parent()
+            .unwrap_or(Path::new(""))
+            .join(&descriptor.image_path);
+        let bytes = std::fs::read(image_path)?;
+        // mq::Image::from_file_with_format panics on a file that isn't an image
+        let image = image::load_from_memory(&bytes)
+            .map_err(|err| AtlasError::Decode(err.to_string()))?
+            .to_rgba8();
+        let image = mq::Image {
+            width: image.width() as u16,
+            height: image.height() as u16,
+            bytes: image.into_raw(),
+        };
+
+        Self::new(descriptor, image)
+    }
+    // for atlases embedded in the binary, the `image:` is ignored
+    pub fn parse(source: &str, image: mq::Image) -> Result<Self, AtlasError> {
+        Self::new(parse_descriptor(source)?, image)
+    }
+    fn new(descriptor: Descriptor, image: mq::Image) -> Result<Self, AtlasError> {
+        let rows = descriptor.rows.unwrap_or_else(|| {
+            let num_textures = descriptor.names.len() as u32;
+            num_textures.div_ceil(descriptor.columns)
+        });
+
+        // in u64, the sizes come from the descriptor and can overflow a u32
+        let wide = |a: u32, b: u32| a as u64 * b as u64;
+
+        // texture ids have to fit in a u8
+        let capacity = wide(descriptor.columns, rows).min(u8::MAX as u64) as u32;
+        if descriptor.names.len() as u32 > capacity {
+            return Err(AtlasError::TooManyTextures { capacity });
+        }
+        if (image.width() as u64) < wide(descriptor.columns, descriptor.tile_width)
+            || (image.height() as u64) < wide(rows, descriptor.tile_height)
+        {
+            return Err(AtlasError::ImageTooSmall);
+        }
+
+        Ok(Self {
+            image,
+            tile_width: descriptor.tile_width,
+            tile_height: descriptor.tile_height,
+            columns: descriptor.columns,
+            names: descriptor.names,
+        })
+    }
+    pub fn len(&self) -> u32 {
+        self.names.len() as u32
+    }
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+    pub fn contains(&self, id: u8) -> bool {
+        id >= 1 && id as u32 <= self.len()
+    }
+    // ids start at 1, 0 is no texture
+    pub fn id(&self, name: &str) -> Option<u8> {
+        self.names
+            .iter()
+            .position(|n| n == name)
+            .map(|index| index as u8 + 1)
+    }
+    // top left pixel of a texture
+    pub fn origin(&self, id: u8) -> (u32, u32) {
+        let index = id as u32 - 1;
+        (
+            (index % self.columns) * self.tile_width,
+            (index / self.columns) * self.tile_height,
+        )
+    }
+}
+
+fn parse_descriptor(source: &str) -> Result<Descriptor, AtlasError> {
+    let mut image_path = None;
+    let mut tile_size = None;
+    let mut grid = None;
+    let mut names: Vec<String> = Vec::new();
+    let mut in_textures = false;
+
+    for (i, line) in source.lines().enumerate() {
+        let line_number = i + 1;
+        let line = line.split('#').next().unwrap_or("").trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        if in_textures {
+            if names.iter().any(|name| name == line) {
+                return Err(AtlasError::DuplicateName {
+                    line: line_number,
+                    name: line.to_owned(),
+                });
+            }
+            names.push(line.to_owned());
+            continue;
+        }
+
+        let (key, value) = line
+            .split_once(':')
+            .ok_or(AtlasError::Syntax { line: line_number })?;
+        let (key, value) = (key.trim(), value.trim());
+        let invalid = || AtlasError::InvalidValue {
+            line: line_number,
+            key: key.to_owned(),
+        };
+        match key {
+            "image" => image_path = Some(value.to_owned()),
+            "tile_size" => tile_size = Some(parse_pair(value).ok_or_else(invalid)?),
+            "grid" => grid = Some(parse_pair(value).ok_or_else(invalid)?),
+            "textures" => in_textures = true,
+            _ => {
+                return Err(AtlasError::UnknownKey {
+                    line: line_number,
+                    key: key.to_owned(),
+                })
+            }
+        }
+    }
+
+    let image_path = image_path.ok_or(AtlasError::MissingImage)?;
+    let (tile_width, tile_height) = tile_size.ok_or(AtlasError::MissingTileSize)?;
+    if names.is_empty() {
+        return Err(AtlasError::NoTextures);
+    }
+    // without a grid the textures are stacked vertically
+    let (columns, rows) = match grid {
+        Some((columns, rows)) => (columns, Some(rows)),
+        None => (1, None),
+    };
+
+    Ok(Descriptor {
+        image_path,
+        tile_width,
+        tile_height,
+        columns,
+        rows,
+        names,
+    })
+}
+
+// two non zero numbers
+fn parse_pair(value: &str) -> Option<(u32, u32)> {
+    let numbers = value
+        .split_whitespace()
+        .map(|n| n.parse::<u32>().ok().filter(|n| *n > 0))
+        .collect::<Option<Vec<u32>>>()?;
+    match numbers[..] {
+        [a, b] => Some((a, b)),
+        _ => None,
+    }
+}
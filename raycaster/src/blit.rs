@@ -0,0 +1,125 @@
+use macroquad::prelude as mq;
+
+use std::ops::Range;
+
+use crate::fog::Fog;
+use crate::SIDE_SHADE;
+
+// the renderer draws straight into [u8; 4] pixels instead of going through
+// mq::Color, the fog and shading are looked up in a Lut instead of being
+// calculated per pixel
+
+pub const FOG_LEVELS: usize = 64;
+
+// 16.16 fixed point, for stepping through a texture
+pub type Fixed = u32;
+const FRACTION_BITS: u32 = 16;
+
+pub fn to_fixed(value: f32) -> Fixed {
+    (value.max(0.0) * (1 << FRACTION_BITS) as f32) as Fixed
+}
+
+// maps every channel value to its value with fog (and shade), [channel][value]
+pub struct Lut([[u8; 256]; 3]);
+impl Lut {
+    fn new(shade: f32, fog_amount: f32, fog_color: mq::Color) -> Self {
+        let background = [fog_color.r, fog_color.g, fog_color.b];
+        let mut table = [[0; 256]; 3];
+        for (channel, values) in table.iter_mut().enumerate() {
+            for (value, out) in values.iter_mut().enumerate() {
+                // shaded, moved fog_amount of the way to the fog color, then the
+                // same conversion as Color -> [u8; 4]
+                let color = value as f32 / 255.0 * shade;
+                let color = color + (background[channel] - color) * fog_amount;
+                *out = (color * 255.0) as u8;
+            }
+        }
+        Self(table)
+    }
+    pub fn apply(&self, pixel: [u8; 4]) -> [u8; 4] {
+        [
+            self.0[0][pixel[0] as usize],
+            self.0[1][pixel[1] as usize],
+            self.0[2][pixel[2] as usize],
+            pixel[3],
+        ]
+    }
+}
+
+// a Lut for every fog level, for both lit and shaded (SIDE_SHADE) walls
+// built once per frame, the level's fog can change while it is being edited
+pub struct FogTable {
+    fog: Fog,
+    lit: Vec<Lut>,
+    shaded: Vec<Lut>,
+}
+impl FogTable {
+    pub fn new(fog: Fog) -> Self {
+        let luts = |shade: f32| {
+            (0..=FOG_LEVELS)
+                .map(|level| Lut::new(shade, level as f32 / FOG_LEVELS as f32, fog.color))
+                .collect()
+        };
+        Self {
+            fog,
+            lit: luts(1.0),
+            shaded: luts(SIDE_SHADE),
+        }
+    }
+    // for something world_distance pixels away from the camera
+    pub fn lut(&self, world_distance: f32, shaded: bool) -> &Lut {
+        let fog_amount = self.fog.amount(world_distance);
+        let level = (fog_amount.clamp(0.0, 1.0) * FOG_LEVELS as f32).round() as usize;
+        if shaded {
+            &self.shaded[level]
+        } else {
+            &self.lit[level]
+        }
+    }
+}
+
+// a single column of texels in an atlas
+pub struct TextureColumn<'a> {
+    texels: &'a [[u8; 4]],
+    stride: usize,
+    x: usize,
+    y0: usize,
+    height: u32,
+}
+impl<'a> TextureColumn<'a> {
+    pub fn new(image: &'a mq::Image, x: u32, y0: u32, height: u32) -> Self {
+        Self {
+            texels: image.get_image_data(),
+            stride: image.width(),
+            x: x as usize,
+            y0: y0 as usize,
+            height,
+        }
+    }
+    fn texel(&self, texture_y: Fixed) -> [u8; 4] {
+        let y = (texture_y >> FRACTION_BITS).min(self.height - 1) as usize;
+        self.texels[(self.y0 + y) * self.stride + self.x]
+    }
+}
+
+// draws rows of column from texture, starting at texture_y and moving step
+// texels per row, pixels equal to transparent are skipped
+pub fn textured_span(
+    column: &mut [[u8; 4]],
+    rows: Range<usize>,
+    texture: &TextureColumn,
+    texture_y: Fixed,
+    step: Fixed,
+    lut: &Lut,
+    transparent: Option<[u8; 4]>,
+) {
+    let mut texture_y = texture_y;
+    for pixel in &mut column[rows] {
+        let texel = texture.texel(texture_y);
+        texture_y = texture_y.wrapping_add(step);
+        if Some(texel) == transparent {
+            continue;
+        }
+        *pixel = lut.apply(texel);
+    }
+}
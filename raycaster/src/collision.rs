@@ -0,0 +1,53 @@
+use macroquad::prelude as mq;
+
+use crate::map::Map;
+use crate::TILE_SIZE;
+
+// times the circle is pushed out of the walls after each step, pushing out of
+// one wall can push it into another one in a corner
+const RESOLVE_ITERATIONS: usize = 3;
+
+// moves a circle (in pixels) by motion, sliding along walls instead of stopping
+// at them, the outside of the map is solid
+//
+// the motion is split into steps of at most half the radius, so the centre
+// never gets inside a wall however fast the circle moves
+pub fn move_circle(map: &Map, pos: mq::Vec2, radius: f32, motion: mq::Vec2) -> mq::Vec2 {
+    let radius = radius.max(1.0);
+    let steps = (motion.length() / (radius / 2.0)).ceil().max(1.0);
+    let step = motion / steps;
+
+    let mut pos = pos;
+    for _ in 0..steps as u32 {
+        pos += step;
+        for _ in 0..RESOLVE_ITERATIONS {
+            pos = push_out(map, pos, radius);
+        }
+    }
+    pos
+}
+
+// pushes the circle out of every solid tile it overlaps, along the direction
+// from the closest point of the tile to the centre
+fn push_out(map: &Map, pos: mq::Vec2, radius: f32) -> mq::Vec2 {
+    let tile_size = TILE_SIZE as f32;
+    let min = ((pos - radius) / tile_size).floor();
+    let max = ((pos + radius) / tile_size).floor();
+
+    let mut pos = pos;
+    for y in min.y as i32..=max.y as i32 {
+        for x in min.x as i32..=max.x as i32 {
+            if !map.is_solid(x, y) {
+                continue;
+            }
+            let tile_min = mq::Vec2::new(x as f32, y as f32) * tile_size;
+            let closest = pos.clamp(tile_min, tile_min + tile_size);
+            let offset = pos - closest;
+            let distance = offset.length();
+            if distance > 0.0 && distance < radius {
+                pos += offset / distance * (radius - distance);
+            }
+        }
+    }
+    pos
+}
@@ -0,0 +1,59 @@
+use macroquad::prelude as mq;
+
+use crate::ray::{GridWalk, Ray, RayHit};
+use crate::{TILE_SIZE, VIEW_DISTANCE};
+
+// tiles the player has seen, for a fog of war on the minimap
+pub struct Explored {
+    pub width: u32,
+    pub height: u32,
+    seen: Vec<bool>,
+}
+impl Explored {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            seen: vec![false; (width * height) as usize],
+        }
+    }
+    // false outside of the map
+    pub fn is_seen(&self, x: i32, y: i32) -> bool {
+        x >= 0
+            && y >= 0
+            && (x as u32) < self.width
+            && (y as u32) < self.height
+            && self.seen[(y as u32 * self.width + x as u32) as usize]
+    }
+    fn see(&mut self, x: i32, y: i32) {
+        if x >= 0 && y >= 0 && (x as u32) < self.width && (y as u32) < self.height {
+            self.seen[(y as u32 * self.width + x as u32) as usize] = true;
+        }
+    }
+    // every tile the ray went through, up to and including the one it hit, or
+    // up to the view distance if it didn't hit anything
+    pub fn reveal(&mut self, ray: &Ray, hit: Option<&RayHit>) {
+        let start = ray.pos / TILE_SIZE as f32;
+        let tile = start.floor();
+        self.see(tile.x as i32, tile.y as i32);
+
+        let direction = ray.direction.normalize_or_zero();
+        if direction == mq::Vec2::ZERO {
+            return;
+        }
+
+        let end = match hit {
+            Some(hit) => {
+                self.see(hit.tile.0, hit.tile.1);
+                hit.world_distance
+            }
+            None => VIEW_DISTANCE,
+        } / TILE_SIZE as f32;
+        for cell in GridWalk::new(start, direction) {
+            if cell.distance > end {
+                break;
+            }
+            self.see(cell.tile.x as i32, cell.tile.y as i32);
+        }
+    }
+}
@@ -0,0 +1,67 @@
+use macroquad::prelude as mq;
+
+use std::fmt;
+
+use crate::{BACKGROUND_COLOR, VIEW_DISTANCE};
+
+// e^-3 is about 5%, so the exponential falloffs are 95% fog at the end distance
+const DENSITY: f32 = 3.0;
+
+// how things fade into the fog between the start and end distances
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Falloff {
+    Linear,             // fully fogged at the end distance
+    Exponential,        // fades quickly after the start, never fully fogged
+    ExponentialSquared, // stays clear for longer, then fades quickly
+}
+impl Falloff {
+    // the name in level files
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "linear" => Some(Falloff::Linear),
+            "exp" => Some(Falloff::Exponential),
+            "exp2" => Some(Falloff::ExponentialSquared),
+            _ => None,
+        }
+    }
+}
+impl fmt::Display for Falloff {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Falloff::Linear => write!(f, "linear"),
+            Falloff::Exponential => write!(f, "exp"),
+            Falloff::ExponentialSquared => write!(f, "exp2"),
+        }
+    }
+}
+
+// distances are in pixels from the camera (a tile is 64 pixels)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Fog {
+    pub falloff: Falloff,
+    pub start: f32,
+    pub end: f32, // more than start
+    pub color: mq::Color,
+}
+impl Default for Fog {
+    // fades in over the second half of the view distance
+    fn default() -> Self {
+        Self {
+            falloff: Falloff::Linear,
+            start: VIEW_DISTANCE / 2.0,
+            end: VIEW_DISTANCE,
+            color: BACKGROUND_COLOR,
+        }
+    }
+}
+impl Fog {
+    // from 0.0 (no fog) to 1.0 (only the fog color) for something distance away
+    pub fn amount(&self, distance: f32) -> f32 {
+        let t = ((distance - self.start) / (self.end - self.start)).max(0.0);
+        match self.falloff {
+            Falloff::Linear => t.min(1.0),
+            Falloff::Exponential => 1.0 - (-DENSITY * t).exp(),
+            Falloff::ExponentialSquared => 1.0 - (-DENSITY * t * t).exp(),
+        }
+    }
+}
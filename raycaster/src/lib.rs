@@ -0,0 +1,37 @@
+// Wolfenstein 3D like raycasting engine, the game in main.rs is built on top of it
+
+use macroquad::prelude as mq;
+
+pub mod atlas;
+mod blit;
+pub mod collision;
+pub mod explored;
+pub mod fog;
+pub mod map;
+pub mod ray;
+pub mod render;
+pub mod settings;
+
+pub const TILE_SIZE: u32 = 64;
+
+// default render resolution, one ray is cast per column
+pub const RENDER_WIDTH: u32 = 512;
+pub const RENDER_HEIGHT: u32 = 512;
+
+// default height of the camera above the floor, the middle of a wall
+pub const EYE_HEIGHT: f32 = TILE_SIZE as f32 / 2.0;
+
+// default horizontal field of view, it can be changed in the settings
+pub const FOV: f32 = std::f32::consts::PI / 2.0;
+
+pub const VIEW_DISTANCE: f32 = 7.0 * TILE_SIZE as f32;
+
+// brightness of walls facing north/south, like Wolfenstein 3D
+pub const SIDE_SHADE: f32 = 0.75;
+
+pub const DOOR_SPEED: f32 = 1.0; // fraction of the door per second
+pub const DOOR_OPEN_TIME: f32 = 3.0; // seconds
+
+pub const BACKGROUND_COLOR: mq::Color = mq::Color::new(73.0 / 255.0, 1.0, 1.0, 1.0);
+pub const GROUND_COLOR: mq::Color = mq::Color::new(36.0 / 255.0, 219.0 / 255.0, 0.0, 1.0);
+pub const SPRITE_TRANSPARENT_COLOR: mq::Color = mq::BLACK;
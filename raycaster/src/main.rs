@@ -1,103 +1,116 @@
 use macroquad::prelude as mq;
+use macroquad::ui::{hash, root_ui, widgets};
 
-const WINDOW_WIDTH: u32 = 1024;
-const WINDOW_HEIGHT: u32 = 512;
-
-const MAP_WIDTH: u32 = 8;
-const MAP_HEIGHT: u32 = 8;
-const TILE_SIZE: u32 = 64;
+use raycaster::collision;
+use raycaster::explored::Explored;
+use raycaster::map::{Map, TileType, DOOR};
+use raycaster::render::{self, Camera, DynamicResolution, Textures};
+use raycaster::settings::{self, Settings};
+use raycaster::{EYE_HEIGHT, FOV, RENDER_HEIGHT, RENDER_WIDTH, TILE_SIZE};
 
-const NUM_RAYS: u32 = 512;
-const RAYS_PER_SECOND: f32 = NUM_RAYS as f32 / 2.0;
+// settings are saved here when --config isn't given
+const DEFAULT_CONFIG_PATH: &str = "raycaster.cfg";
+// the editor saves here when the built in level is being edited
+const DEFAULT_LEVEL_PATH: &str = "level.map";
 
-const FOV: f32 = std::f32::consts::PI / 2.0;
-
-const MOUSE_SENSITIVITY: f32 = 0.001;
+const WINDOW_WIDTH: u32 = 1024;
+const WINDOW_HEIGHT: u32 = 512;
 
-const VIEW_DISTANCE: f32 = 7.0 * TILE_SIZE as f32;
+const LOADING_TIME: f32 = 2.0; // seconds to draw every column
 
-const NUM_TEXTURES: i32 = 3;
+// time to render a frame before dynamic resolution scales down (in seconds)
+const RENDER_BUDGET: f32 = 1.0 / 120.0;
 
-const BACKGROUND_COLOR: mq::Color = mq::Color::new(73.0 / 255.0, 1.0, 1.0, 1.0);
-const GROUND_COLOR: mq::Color = mq::Color::new(36.0 / 255.0, 219.0 / 255.0, 0.0, 1.0);
 const WALL_COLOR_LIGHT: mq::Color = mq::Color::new(0.6, 0.6, 0.6, 1.0);
 const WALL_COLOR_DARK: mq::Color = mq::Color::new(0.55, 0.55, 0.55, 1.0);
+const MINIMAP_BACKGROUND: mq::Color = mq::Color::new(0.0, 0.0, 0.0, 0.6);
+
+// tiles from the centre to the edge of the rotating minimap
+const ROTATING_MINIMAP_TILES: f32 = 5.0;
+
 const NORD_COLOR: mq::Color = mq::Color::new(46.0 / 255.0, 52.0 / 255.0, 64.0 / 255.0, 1.0);
 
+// heights are in pixels, a tile is 64 pixels tall
+const GRAVITY: f32 = 600.0; // pixels per second squared
+const JUMP_SPEED: f32 = 170.0; // pixels per second, about 24 pixels high
+const CROUCH_EYE_HEIGHT: f32 = 16.0;
+const CROUCH_SPEED: f32 = 128.0; // pixels per second the eye moves when (un)crouching
+const CROUCH_MOVE_SPEED: f32 = 0.5; // fraction of the move speed
+const HEAD_BOB_HEIGHT: f32 = 2.0;
+const HEAD_BOB_LENGTH: f32 = 80.0; // pixels walked per bob
+const HEAD_BOB_FADE: f32 = 8.0; // fraction per second it fades in and out
+
 struct Player {
     pos: mq::Vec2,
     direction: mq::Vec2,
     angle: f32,          // in radians
     angle_vertical: f32, // in radians
 
+    // the camera is eye_height + jump_height + the head bob above the floor
+    eye_height: f32, // lower while crouching
+    jump_height: f32,
+    vertical_speed: f32,
+    bob_distance: f32, // walked while bobbing
+    bob_amount: f32,   // 0.0 standing still to 1.0 walking
+
     last_mouse_pos: mq::Vec2,
 }
 impl Player {
-    fn new(pos: mq::Vec2) -> Self {
+    fn new(pos: mq::Vec2, angle: f32) -> Self {
         Self {
             pos,
-            angle: 0.0,
+            angle,
             angle_vertical: 0.0,
-            direction: mq::Vec2::new(1.0, 0.0),
+            direction: mq::Vec2::new(angle.cos(), angle.sin()),
+            eye_height: EYE_HEIGHT,
+            jump_height: 0.0,
+            vertical_speed: 0.0,
+            bob_distance: 0.0,
+            bob_amount: 0.0,
             last_mouse_pos: mq::mouse_position().into(),
         }
     }
-    fn draw(&self, scaling_info: &ScalingInfo) {
-        mq::draw_circle(
-            scaling_info.offset.x + self.pos.x * scaling_info.width / WINDOW_WIDTH as f32,
-            scaling_info.offset.y + self.pos.y * scaling_info.height / WINDOW_HEIGHT as f32,
-            8.0,
-            mq::YELLOW,
-        );
+    fn camera(&self, settings: &Settings) -> Camera {
+        let bob = (self.bob_distance / HEAD_BOB_LENGTH * std::f32::consts::TAU).sin()
+            * HEAD_BOB_HEIGHT
+            * self.bob_amount;
+        Camera {
+            pos: self.pos,
+            angle: self.angle,
+            angle_vertical: self.angle_vertical,
+            fov: settings.fov_radians(),
+            height: self.eye_height + self.jump_height + bob,
+        }
+    }
+    fn draw(&self, minimap_info: &MinimapInfo) {
+        let screen_pos = minimap_info.world_to_screen(self.pos);
+        let radius = (minimap_info.tile_size / 4.0).clamp(2.0, 8.0);
+        // the minimap might be rotated
+        let direction = (minimap_info.world_to_screen(self.pos + self.direction) - screen_pos)
+            .normalize_or_zero();
+        mq::draw_circle(screen_pos.x, screen_pos.y, radius, mq::YELLOW);
         mq::draw_line(
-            scaling_info.offset.x + self.pos.x * scaling_info.width / WINDOW_WIDTH as f32,
-            scaling_info.offset.y + self.pos.y * scaling_info.height / WINDOW_HEIGHT as f32,
-            scaling_info.offset.x
-                + self.pos.x * scaling_info.width / WINDOW_WIDTH as f32
-                + self.angle.cos() * 20.0,
-            scaling_info.offset.y
-                + self.pos.y * scaling_info.height / WINDOW_HEIGHT as f32
-                + self.angle.sin() * 20.0,
+            screen_pos.x,
+            screen_pos.y,
+            screen_pos.x + direction.x * radius * 2.5,
+            screen_pos.y + direction.y * radius * 2.5,
             3.0,
             mq::YELLOW,
         );
     }
-    fn touching_wall(&mut self, move_vec: mq::Vec2, delta: f32, map: &[u8]) {
-        let move_x = move_vec.x * 100.0 * delta;
-        let move_y = move_vec.y * 100.0 * delta;
-
-        self.pos.x += move_x;
-        let map_x = (self.pos.x / TILE_SIZE as f32) as usize;
-        let map_y = (self.pos.y / TILE_SIZE as f32) as usize;
-        let map_index = map_y * MAP_WIDTH as usize + map_x;
-
-        if map[map_index] != 0 {
-            self.pos.x -= move_x;
-        }
-
-        self.pos.y += move_y;
-        let map_x = (self.pos.x / TILE_SIZE as f32) as usize;
-        let map_y = (self.pos.y / TILE_SIZE as f32) as usize;
-        let map_index = map_y * MAP_WIDTH as usize + map_x;
-
-        if map[map_index] != 0 {
-            self.pos.y -= move_y;
-        }
-    }
-    fn input(&mut self, delta: f32, mouse_grabbed: bool, map: &[u8]) {
+    fn input(&mut self, delta: f32, mouse_grabbed: bool, map: &Map, settings: &Settings) {
         if mq::is_key_down(mq::KeyCode::Left) {
-            self.angle -= 3.0 * delta;
+            self.angle -= settings.turn_speed * delta;
         }
         if mq::is_key_down(mq::KeyCode::Right) {
-            self.angle += 3.0 * delta;
+            self.angle += settings.turn_speed * delta;
         }
 
-        // 2.1 = slightly less than 90 degrees
         if mq::is_key_down(mq::KeyCode::Up) {
-            self.angle_vertical += 3.0 * delta;
+            self.angle_vertical += settings.turn_speed * delta;
         }
         if mq::is_key_down(mq::KeyCode::Down) {
-            self.angle_vertical -= 3.0 * delta;
+            self.angle_vertical -= settings.turn_speed * delta;
         }
 
         let mouse_position: mq::Vec2 = mq::mouse_position().into();
@@ -105,8 +118,8 @@ impl Player {
         self.last_mouse_pos = mouse_position;
 
         if mouse_grabbed {
-            self.angle += mouse_delta.x * MOUSE_SENSITIVITY;
-            self.angle_vertical -= mouse_delta.y * MOUSE_SENSITIVITY;
+            self.angle += mouse_delta.x * settings.mouse_sensitivity;
+            self.angle_vertical -= mouse_delta.y * settings.mouse_sensitivity;
         }
 
         if self.angle < 0.0 {
@@ -114,11 +127,8 @@ impl Player {
         } else if self.angle > 2.0 * std::f32::consts::PI {
             self.angle -= 2.0 * std::f32::consts::PI;
         }
-        if self.angle_vertical > std::f32::consts::PI / 2.1 {
-            self.angle_vertical = std::f32::consts::PI / 2.1;
-        } else if self.angle_vertical < -std::f32::consts::PI / 2.1 {
-            self.angle_vertical = -std::f32::consts::PI / 2.1;
-        }
+        let max_pitch = settings.max_pitch_radians();
+        self.angle_vertical = self.angle_vertical.clamp(-max_pitch, max_pitch);
 
         self.direction = mq::Vec2::new(self.angle.cos(), self.angle.sin());
 
@@ -136,220 +146,105 @@ impl Player {
             move_vec -= mq::Vec2::new(self.direction.y, -self.direction.x);
         }
 
+        let crouching =
+            mq::is_key_down(mq::KeyCode::C) || mq::is_key_down(mq::KeyCode::LeftControl);
         if move_vec.length() > 0.0 {
-            move_vec = move_vec.normalize();
-            self.touching_wall(move_vec, delta, map);
-
-            if self.pos.x < 0.0 {
-                self.pos.x = 0.0;
-            } else if self.pos.x > MAP_WIDTH as f32 * TILE_SIZE as f32 {
-                self.pos.x = MAP_WIDTH as f32 * TILE_SIZE as f32;
-            }
-
-            if self.pos.y < 0.0 {
-                self.pos.y = 0.0;
-            } else if self.pos.y > MAP_HEIGHT as f32 * TILE_SIZE as f32 {
-                self.pos.y = MAP_HEIGHT as f32 * TILE_SIZE as f32;
-            }
+            let speed = if crouching {
+                settings.move_speed * CROUCH_MOVE_SPEED
+            } else {
+                settings.move_speed
+            };
+            move_vec = move_vec.normalize() * speed * delta;
         }
-    }
-    fn cast_rays(&self, map: &[u8], num_rays: u32) -> Vec<(Ray, Option<RayHit>)> {
-        let rotation_matrix = mq::Mat2::from_angle(self.angle);
-        (0..num_rays)
-            .map(|i| {
-                let unrotated_direction =
-                    mq::Vec2::new(1.0, (i as f32 / NUM_RAYS as f32 - 0.5) * FOV);
-                let direction = rotation_matrix * unrotated_direction;
-                let ray = Ray::new(self.pos, direction);
-                ray.cast_ray(map)
-            })
-            .collect()
-    }
-}
+        // also runs while standing still, so closing doors push the player out
+        let old_pos = self.pos;
+        self.pos = collision::move_circle(map, self.pos, settings.player_radius, move_vec);
 
-struct RayHit {
-    pos: mq::Vec2,
-    world_distance: f32,
-    x_move: bool,
-    wall_coord: f32, // 0-1.0 as x
-    wall_type: u8,
-}
-#[derive(Clone, Copy)]
-struct Ray {
-    pos: mq::Vec2,
-    angle: f32,
-    direction: mq::Vec2,
-}
-impl Ray {
-    fn new(pos: mq::Vec2, direction: mq::Vec2) -> Self {
-        Self {
-            pos,
-            angle: direction.y.atan2(direction.x),
-            direction,
-        }
+        self.vertical(delta, crouching, self.pos.distance(old_pos));
     }
-    fn cast_ray(&self, map: &[u8]) -> (Ray, Option<RayHit>) {
-        // DDA algorithm
-        let x = self.pos.x / TILE_SIZE as f32; // (0.0, 8.0)
-        let y = self.pos.y / TILE_SIZE as f32; // (0.0, 8.0)
-        let ray_start = mq::Vec2::new(x, y);
-
-        let ray_dir = self.direction.normalize();
-
-        let ray_unit_step_size = mq::Vec2::new(
-            (1.0 + (ray_dir.y / ray_dir.x).powi(2)).sqrt(),
-            (1.0 + (ray_dir.x / ray_dir.y).powi(2)).sqrt(),
-        );
-        let mut map_check = ray_start.floor();
-        let mut ray_length_1d = mq::Vec2::ZERO;
-        let mut step = mq::Vec2::ZERO;
-
-        if ray_dir.x < 0.0 {
-            step.x = -1.0;
-            ray_length_1d.x = (x - map_check.x) * ray_unit_step_size.x;
+    // crouching, jumping and the head bob, walked is the distance moved this frame
+    fn vertical(&mut self, delta: f32, crouching: bool, walked: f32) {
+        let target_eye_height = if crouching {
+            CROUCH_EYE_HEIGHT
         } else {
-            step.x = 1.0;
-            ray_length_1d.x = (map_check.x + 1.0 - x) * ray_unit_step_size.x;
+            EYE_HEIGHT
+        };
+        let eye_change = (target_eye_height - self.eye_height)
+            .clamp(-CROUCH_SPEED * delta, CROUCH_SPEED * delta);
+        self.eye_height += eye_change;
+
+        let on_ground = self.jump_height <= 0.0;
+        if on_ground && !crouching && mq::is_key_pressed(mq::KeyCode::Space) {
+            self.vertical_speed = JUMP_SPEED;
         }
-
-        if ray_dir.y < 0.0 {
-            step.y = -1.0;
-            ray_length_1d.y = (y - map_check.y) * ray_unit_step_size.y;
-        } else {
-            step.y = 1.0;
-            ray_length_1d.y = (map_check.y + 1.0 - y) * ray_unit_step_size.y;
-        }
-
-        let max_distance = 100.0;
-        let mut distance = 0.0;
-        let mut x_move;
-        while distance < max_distance {
-            if ray_length_1d.x < ray_length_1d.y {
-                map_check.x += step.x;
-                distance = ray_length_1d.x;
-                ray_length_1d.x += ray_unit_step_size.x;
-                x_move = true;
-            } else {
-                map_check.y += step.y;
-                distance = ray_length_1d.y;
-                ray_length_1d.y += ray_unit_step_size.y;
-                x_move = false;
-            }
-
-            if map_check.x >= 0.0
-                && map_check.x < MAP_WIDTH as f32
-                && map_check.y >= 0.0
-                && map_check.y < MAP_HEIGHT as f32
-            {
-                let map_index = (map_check.y * MAP_WIDTH as f32 + map_check.x) as usize;
-                let wall_type = map[map_index];
-                if wall_type != 0 {
-                    // 0 = no wall
-                    let pos = self.pos + (ray_dir * distance * TILE_SIZE as f32);
-
-                    let map_pos = pos / TILE_SIZE as f32;
-                    let wall_pos = map_pos - map_pos.floor();
-                    let wall_coord = if x_move { wall_pos.y } else { wall_pos.x };
-
-                    return (
-                        *self,
-                        Some(RayHit {
-                            pos,
-                            world_distance: distance * TILE_SIZE as f32,
-                            x_move,
-                            wall_coord,
-                            wall_type,
-                        }),
-                    );
-                }
-            }
+        self.vertical_speed -= GRAVITY * delta;
+        self.jump_height = (self.jump_height + self.vertical_speed * delta).max(0.0);
+        if self.jump_height <= 0.0 {
+            self.vertical_speed = 0.0;
         }
 
-        (*self, None)
-    }
-}
-
-trait Lerp {
-    fn lerp(self, other: Self, t: f32) -> Self;
-}
-impl Lerp for mq::Color {
-    fn lerp(self, other: Self, t: f32) -> Self {
-        let t = t.clamp(0.0, 1.0);
-        mq::Color {
-            r: self.r + (other.r - self.r) * t,
-            g: self.g + (other.g - self.g) * t,
-            b: self.b + (other.b - self.b) * t,
-            a: self.a + (other.a - self.a) * t,
+        // only bobs while walking on the ground, and fades out when stopping
+        let walking = on_ground && walked > 0.0;
+        if walking {
+            self.bob_distance += walked;
         }
+        let fade = if walking { 1.0 } else { -1.0 } * HEAD_BOB_FADE * delta;
+        self.bob_amount = (self.bob_amount + fade).clamp(0.0, 1.0);
     }
 }
 
-fn draw_map(map: &[u8], scaling_info: &ScalingInfo) {
-    let scaled_size = scaling_info.width / (MAP_WIDTH as f32 * 2.0);
-    for y in 0..MAP_HEIGHT {
-        for x in 0..MAP_WIDTH {
-            let wall = map[(y * MAP_WIDTH + x) as usize];
+// unseen tiles aren't drawn when there is a fog of war
+fn draw_map(map: &Map, minimap_info: &MinimapInfo, explored: Option<&Explored>) {
+    let scaled_size = minimap_info.tile_size;
+    // tiny tiles would disappear with a 1px border on each side
+    let inset = if scaled_size > 4.0 {
+        1.0 / scaled_size
+    } else {
+        0.0
+    };
+    let hidden = |x: i32, y: i32| explored.is_some_and(|explored| !explored.is_seen(x, y));
+    for y in 0..map.height as i32 {
+        for x in 0..map.width as i32 {
+            let tile = mq::Vec2::new(x as f32, y as f32);
+            let centre = minimap_info.tile_to_screen(tile + 0.5);
+            if hidden(x, y) || !minimap_info.is_visible(centre, scaled_size * 0.71) {
+                continue;
+            }
+            let wall = map.tile(x, y);
             let color = match wall {
-                1 => mq::BLUE,
-                2 => mq::RED,
-                3 => mq::GREEN,
-                _ => mq::BLACK,
+                0 => mq::BLACK,
+                DOOR => mq::BROWN,
+                _ => match map.tile_type(wall).north {
+                    1 => mq::BLUE,
+                    2 => mq::RED,
+                    3 => mq::GREEN,
+                    _ => mq::GRAY,
+                },
             };
-            mq::draw_rectangle(
-                scaling_info.offset.x + x as f32 * scaled_size + 1.0,
-                scaling_info.offset.y + y as f32 * scaled_size + 1.0,
-                scaled_size - 2.0,
-                scaled_size - 2.0,
-                color,
-            );
+            // two triangles, so the tile can be rotated
+            let corners = [
+                mq::Vec2::new(inset, inset),
+                mq::Vec2::new(1.0 - inset, inset),
+                mq::Vec2::new(1.0 - inset, 1.0 - inset),
+                mq::Vec2::new(inset, 1.0 - inset),
+            ]
+            .map(|corner| minimap_info.tile_to_screen(tile + corner));
+            mq::draw_triangle(corners[0], corners[1], corners[2], color);
+            mq::draw_triangle(corners[0], corners[2], corners[3], color);
         }
     }
-}
-
-struct VerticalLine {
-    x: i32,
-    y0: i32,
-    y1: i32,
-}
-impl VerticalLine {
-    fn new(x: i32, y0: i32, y1: i32) -> Self {
-        Self { x, y0, y1 }
-    }
-}
-fn vertical_line(line: VerticalLine, output_image: &mut mq::Image, color: mq::Color) {
-    let x = line.x.clamp(0, output_image.width() as i32 - 1) as u32;
-    let y0 = line.y0.clamp(0, output_image.height() as i32 - 1) as u32;
-    let y1 = line.y1.clamp(0, output_image.height() as i32 - 1) as u32;
-
-    for y in y0..y1 {
-        output_image.set_pixel(x, y, color);
-    }
-}
-
-fn vertical_textured_line_with_fog(
-    wall_line: VerticalLine,
-    output_image: &mut mq::Image,
-    texture: &mq::Image,
-    texture_line: VerticalLine,
-    fog_brightness: f32,
-) {
-    let draw_x = wall_line.x.clamp(0, output_image.width() as i32 - 1) as u32;
-    let draw_y0 = wall_line.y0.clamp(0, output_image.height() as i32 - 1) as u32;
-    let draw_y1 = wall_line.y1.clamp(0, output_image.height() as i32 - 1) as u32;
-
-    let texture_x = texture_line.x.clamp(0, texture.width() as i32 - 1) as u32;
-
-    let h = wall_line.y1 - wall_line.y0;
-    let texture_h = texture_line.y1 - texture_line.y0;
-
-    for y in draw_y0..draw_y1 {
-        let h_ratio = texture_h as f32 / h as f32;
-        let h_diff = y as i32 - wall_line.y0;
-        let texture_y = (h_diff as f32 * h_ratio) as u32 + texture_line.y0 as u32;
-
-        let color = texture.get_pixel(texture_x, texture_y);
-        let color_with_fog = color.lerp(BACKGROUND_COLOR, fog_brightness);
-        output_image.set_pixel(draw_x, y, color_with_fog);
+    for sprite in map.sprites.iter() {
+        let screen_pos = minimap_info.world_to_screen(sprite.pos * TILE_SIZE as f32);
+        let tile = sprite.pos.floor();
+        if hidden(tile.x as i32, tile.y as i32) || !minimap_info.is_visible(screen_pos, 0.0) {
+            continue;
+        }
+        mq::draw_circle(
+            screen_pos.x,
+            screen_pos.y,
+            (scaled_size / 8.0).max(1.0),
+            mq::ORANGE,
+        );
     }
 }
 
@@ -389,47 +284,540 @@ impl ScalingInfo {
     }
 }
 
-#[macroquad::main(window_conf)]
-async fn main() {
-    let mut player = Player::new(mq::Vec2::new(
-        WINDOW_WIDTH as f32 / 4.0 + TILE_SIZE as f32 / 2.0,
-        WINDOW_HEIGHT as f32 / 2.0 + TILE_SIZE as f32 / 2.0,
-    ));
+#[derive(Clone, Copy, PartialEq)]
+enum MinimapMode {
+    Side,     // the whole map, next to the 3D view
+    Corner,   // the whole map, on top of the 3D view
+    Rotating, // around the player, turned so they face up
+}
+impl MinimapMode {
+    fn next(self) -> Self {
+        match self {
+            MinimapMode::Side => MinimapMode::Corner,
+            MinimapMode::Corner => MinimapMode::Rotating,
+            MinimapMode::Rotating => MinimapMode::Side,
+        }
+    }
+}
+
+// where the map is drawn, screen = origin + rotation * (tile - pivot) * tile_size
+struct MinimapInfo {
+    tile_size: f32, // size of a tile on screen
+    origin: mq::Vec2,
+    pivot: mq::Vec2,          // in tiles
+    rotation: mq::Vec2,       // unit vector, like Vec2::from_angle
+    clip_radius: Option<f32>, // only drawn this close to origin
+}
+impl MinimapInfo {
+    // fits the whole map into a rectangle, keeping tiles square
+    fn fit(map: &Map, position: mq::Vec2, size: mq::Vec2) -> MinimapInfo {
+        let map_size = mq::Vec2::new(map.width as f32, map.height as f32);
+        let tile_size = (size.x / map_size.x).min(size.y / map_size.y);
+        let origin = position + (size - map_size * tile_size) / 2.0;
+
+        MinimapInfo {
+            tile_size,
+            origin,
+            pivot: mq::Vec2::ZERO,
+            rotation: mq::Vec2::X,
+            clip_radius: None,
+        }
+    }
+    // a circle around the player, their direction is up
+    fn rotating(player: &Player, centre: mq::Vec2, radius: f32) -> MinimapInfo {
+        MinimapInfo {
+            tile_size: radius / ROTATING_MINIMAP_TILES,
+            origin: centre,
+            pivot: player.pos / TILE_SIZE as f32,
+            rotation: mq::Vec2::from_angle(-std::f32::consts::FRAC_PI_2 - player.angle),
+            clip_radius: Some(radius),
+        }
+    }
+    fn tile_to_screen(&self, tile: mq::Vec2) -> mq::Vec2 {
+        self.origin + self.rotation.rotate(tile - self.pivot) * self.tile_size
+    }
+    fn world_to_screen(&self, pos: mq::Vec2) -> mq::Vec2 {
+        self.tile_to_screen(pos / TILE_SIZE as f32)
+    }
+    fn screen_to_tile(&self, screen_pos: mq::Vec2, map: &Map) -> Option<(u32, u32)> {
+        let inverse = mq::Vec2::new(self.rotation.x, -self.rotation.y);
+        let pos = self.pivot + inverse.rotate(screen_pos - self.origin) / self.tile_size;
+        if pos.x < 0.0 || pos.y < 0.0 || pos.x >= map.width as f32 || pos.y >= map.height as f32 {
+            return None;
+        }
+        Some((pos.x as u32, pos.y as u32))
+    }
+    // inside the clip circle, at least margin away from its edge
+    fn is_visible(&self, screen_pos: mq::Vec2, margin: f32) -> bool {
+        self.clip_radius
+            .is_none_or(|radius| screen_pos.distance(self.origin) <= radius - margin)
+    }
+}
+
+struct Args {
+    map_path: Option<String>,
+    render_path: Option<String>,
+    camera: Option<Camera>,
+    textures_path: Option<String>,
+    resolution: (u32, u32),
+    dynamic_resolution: bool,
+    config_path: Option<String>,
+}
+impl Args {
+    fn parse() -> Result<Args, String> {
+        let mut args = Args {
+            map_path: None,
+            render_path: None,
+            camera: None,
+            textures_path: None,
+            resolution: (RENDER_WIDTH, RENDER_HEIGHT),
+            dynamic_resolution: false,
+            config_path: None,
+        };
+        let mut env_args = std::env::args().skip(1);
+        while let Some(arg) = env_args.next() {
+            match arg.as_str() {
+                "--render" => {
+                    args.render_path = Some(env_args.next().ok_or("--render needs a path")?);
+                }
+                "--camera" => {
+                    let camera = env_args.next().ok_or("--camera needs a value")?;
+                    args.camera = Some(parse_camera(&camera)?);
+                }
+                "--textures" => {
+                    args.textures_path =
+                        Some(env_args.next().ok_or("--textures needs a directory")?);
+                }
+                "--resolution" => {
+                    let resolution = env_args.next().ok_or("--resolution needs a value")?;
+                    args.resolution = parse_resolution(&resolution)?;
+                }
+                "--dynamic-resolution" => args.dynamic_resolution = true,
+                "--config" => {
+                    args.config_path = Some(env_args.next().ok_or("--config needs a path")?);
+                }
+                _ if arg.starts_with("--") => return Err(format!("unknown option `{}`", arg)),
+                _ => args.map_path = Some(arg),
+            }
+        }
+        Ok(args)
+    }
+}
+
+// x,y (in tiles),angle,angle_vertical (in degrees) and optionally the height
+// above the floor (in tiles, less than 1)
+fn parse_camera(value: &str) -> Result<Camera, String> {
+    let numbers = value
+        .split(',')
+        .map(|n| n.trim().parse::<f32>())
+        .collect::<Result<Vec<f32>, _>>()
+        .map_err(|_| format!("invalid camera `{}`", value))?;
+    let height = match numbers[..] {
+        [_, _, _, _] => EYE_HEIGHT,
+        [_, _, _, _, height] if height > 0.0 && height < 1.0 => height * TILE_SIZE as f32,
+        _ => {
+            return Err(format!(
+                "invalid camera `{}`, expected x,y,angle,angle_vertical[,height]",
+                value
+            ))
+        }
+    };
+    Ok(Camera {
+        pos: mq::Vec2::new(numbers[0], numbers[1]) * TILE_SIZE as f32,
+        angle: numbers[2].to_radians(),
+        angle_vertical: numbers[3].to_radians(),
+        fov: FOV,
+        height,
+    })
+}
+
+// widthxheight, like 640x360
+fn parse_resolution(value: &str) -> Result<(u32, u32), String> {
+    let invalid = || format!("invalid resolution `{}`, expected widthxheight", value);
+    let (width, height) = value.split_once('x').ok_or_else(invalid)?;
+    let width = width.trim().parse::<u16>().map_err(|_| invalid())?;
+    let height = height.trim().parse::<u16>().map_err(|_| invalid())?;
+    if width == 0 || height == 0 {
+        return Err(invalid());
+    }
+    Ok((width as u32, height as u32))
+}
+
+// renders a single frame without opening a window
+fn render_to_png(
+    map: &Map,
+    camera: &Camera,
+    textures: &Textures,
+    (width, height): (u32, u32),
+    path: &str,
+) {
+    let mut output_image = mq::Image::gen_image_color(width as u16, height as u16, NORD_COLOR);
+    render::render_frame(&mut output_image, map, camera, textures, width);
+    if let Err(err) = render::save_png(&output_image, path) {
+        eprintln!("failed to save render: {}", err);
+        std::process::exit(1);
+    }
+}
+
+fn main() {
+    let args = Args::parse().unwrap_or_else(|err| {
+        eprintln!("{}", err);
+        eprintln!(
+            "usage: raycaster [level.map] [--textures dir] [--resolution widthxheight] [--dynamic-resolution] [--config settings.cfg] [--render out.png] [--camera x,y,angle,angle_vertical[,height]]"
+        );
+        std::process::exit(1);
+    });
+
+    let map = match &args.map_path {
+        Some(path) => Map::load(path),
+        None => Map::parse(include_str!("../resources/maps/default.map")),
+    }
+    .unwrap_or_else(|err| {
+        eprintln!("failed to load map: {}", err);
+        std::process::exit(1);
+    });
+
+    let textures = match &args.textures_path {
+        Some(path) => Textures::load_dir(path),
+        None => Ok(Textures::load()),
+    }
+    .unwrap_or_else(|err| {
+        eprintln!("failed to load textures: {}", err);
+        std::process::exit(1);
+    });
+
+    if let Err(err) = map.check_textures(&textures) {
+        eprintln!("failed to load map: {}", err);
+        std::process::exit(1);
+    }
+
+    // a headless render only uses a settings file when it's asked for
+    let config_path = match (&args.config_path, &args.render_path) {
+        (Some(path), _) => Some(path.as_str()),
+        (None, Some(_)) => None,
+        (None, None) => Some(DEFAULT_CONFIG_PATH),
+    };
+    // only a settings file that was asked for is worth stopping over
+    let settings = config_path
+        .map_or(Ok(Settings::default()), Settings::load)
+        .unwrap_or_else(|err| {
+            eprintln!("failed to load settings: {}", err);
+            if args.config_path.is_some() {
+                std::process::exit(1);
+            }
+            Settings::default()
+        });
+
+    if let Some(render_path) = &args.render_path {
+        let camera = args.camera.unwrap_or(Camera {
+            pos: map.spawn * TILE_SIZE as f32,
+            angle: map.spawn_angle,
+            angle_vertical: 0.0,
+            fov: FOV,
+            height: EYE_HEIGHT,
+        });
+        let max_pitch = settings.max_pitch_radians();
+        let camera = Camera {
+            fov: settings.fov_radians(),
+            angle_vertical: camera.angle_vertical.clamp(-max_pitch, max_pitch),
+            ..camera
+        };
+        render_to_png(&map, &camera, &textures, args.resolution, render_path);
+        return;
+    }
+
+    let config_path = config_path.unwrap_or(DEFAULT_CONFIG_PATH).to_owned();
+    let level_path = args.map_path.unwrap_or(DEFAULT_LEVEL_PATH.to_owned());
+    macroquad::Window::from_config(
+        window_conf(),
+        run(
+            map,
+            textures,
+            args.resolution,
+            args.dynamic_resolution,
+            settings,
+            config_path,
+            level_path,
+        ),
+    );
+}
+
+// sliders for the settings, in the middle of the 3D view
+fn settings_menu(settings: &mut Settings, scaling_info: &ScalingInfo) {
+    let size = mq::Vec2::new(360.0, 240.0);
+    let position = scaling_info.offset
+        + mq::Vec2::new(scaling_info.width * 0.75, scaling_info.height / 2.0)
+        - size / 2.0;
+    let range = |range: std::ops::RangeInclusive<f32>, scale: f32| {
+        *range.start() * scale..*range.end() * scale
+    };
+
+    widgets::Window::new(hash!(), position, size)
+        .label("Settings")
+        .movable(false)
+        .ui(&mut root_ui(), |ui| {
+            ui.slider(
+                hash!(),
+                "FOV",
+                range(settings::FOV_RANGE, 1.0),
+                &mut settings.fov,
+            );
+            // shown per 1000 pixels so the slider has readable numbers
+            let mut mouse_sensitivity = settings.mouse_sensitivity * 1000.0;
+            ui.slider(
+                hash!(),
+                "Mouse",
+                range(settings::MOUSE_SENSITIVITY_RANGE, 1000.0),
+                &mut mouse_sensitivity,
+            );
+            settings.mouse_sensitivity = mouse_sensitivity / 1000.0;
+            ui.slider(
+                hash!(),
+                "Move speed",
+                range(settings::MOVE_SPEED_RANGE, 1.0),
+                &mut settings.move_speed,
+            );
+            ui.slider(
+                hash!(),
+                "Turn speed",
+                range(settings::TURN_SPEED_RANGE, 1.0),
+                &mut settings.turn_speed,
+            );
+            ui.slider(
+                hash!(),
+                "Player radius",
+                range(settings::PLAYER_RADIUS_RANGE, 1.0),
+                &mut settings.player_radius,
+            );
+            ui.slider(
+                hash!(),
+                "Max pitch",
+                range(settings::MAX_PITCH_RANGE, 1.0),
+                &mut settings.max_pitch,
+            );
+            if ui.button(None, "Defaults") {
+                *settings = Settings::default();
+            }
+            ui.label(None, "Escape to close");
+        });
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Brush {
+    Tile(u8), // wall id or DOOR
+    Spawn,
+}
+
+// paints on the minimap, left click paints with the brush and right click erases
+struct Editor {
+    brushes: Vec<Brush>,
+    brush_names: Vec<String>,
+    brush: usize,
+    save_path: String,
+    status: String, // result of the last save
+}
+impl Editor {
+    fn new(map: &Map, textures: &Textures, save_path: String) -> Self {
+        // every wall texture, and the tiles the level defines itself
+        let mut brushes = Vec::new();
+        let mut brush_names = Vec::new();
+        for id in 1..DOOR {
+            let tile_type = map.tile_types[id as usize];
+            let name = match tile_type {
+                Some(tile_type) if tile_type != TileType::uniform(id) => format!(
+                    "{}: {} {} {} {} x{}",
+                    id,
+                    tile_type.north,
+                    tile_type.south,
+                    tile_type.east,
+                    tile_type.west,
+                    tile_type.height
+                ),
+                _ if textures.wall.contains(id) => {
+                    format!("{}: {}", id, textures.wall.names[id as usize - 1])
+                }
+                _ => continue,
+            };
+            brushes.push(Brush::Tile(id));
+            brush_names.push(name);
+        }
+        brushes.extend([Brush::Tile(DOOR), Brush::Spawn]);
+        brush_names.extend(["Door".to_owned(), "Spawn".to_owned()]);
+
+        Self {
+            brushes,
+            brush_names,
+            brush: 0,
+            save_path,
+            status: String::new(),
+        }
+    }
+    // the player's tile is never painted over, so they can't get stuck in a wall
+    fn paint(&self, map: &mut Map, minimap_info: &MinimapInfo, player: &Player) {
+        let erase = mq::is_mouse_button_down(mq::MouseButton::Right);
+        if !erase && !mq::is_mouse_button_down(mq::MouseButton::Left) {
+            return;
+        }
+        let mouse_position: mq::Vec2 = mq::mouse_position().into();
+        if root_ui().is_mouse_over(mouse_position) {
+            return;
+        }
+        let Some((x, y)) = minimap_info.screen_to_tile(mouse_position, map) else {
+            return;
+        };
+        let player_tile = (player.pos / TILE_SIZE as f32).as_uvec2();
+
+        match (erase, self.brushes[self.brush]) {
+            (true, _) => map.set_tile(x, y, 0),
+            (false, Brush::Tile(id))
+                if (x, y) != (player_tile.x, player_tile.y)
+                    && map.tile(x as i32, y as i32) != id =>
+            {
+                map.set_tile(x, y, id)
+            }
+            (false, Brush::Spawn) if map.tile(x as i32, y as i32) == 0 => {
+                map.spawn = mq::Vec2::new(x as f32 + 0.5, y as f32 + 0.5);
+            }
+            _ => {}
+        }
+    }
+    fn draw(&self, map: &Map, minimap_info: &MinimapInfo) {
+        let spawn = minimap_info.world_to_screen(map.spawn * TILE_SIZE as f32);
+        mq::draw_circle_lines(
+            spawn.x,
+            spawn.y,
+            (minimap_info.tile_size / 3.0).max(2.0),
+            2.0,
+            mq::GREEN,
+        );
+
+        let mouse_position: mq::Vec2 = mq::mouse_position().into();
+        if let Some((x, y)) = minimap_info.screen_to_tile(mouse_position, map) {
+            mq::draw_rectangle_lines(
+                minimap_info.origin.x + x as f32 * minimap_info.tile_size,
+                minimap_info.origin.y + y as f32 * minimap_info.tile_size,
+                minimap_info.tile_size,
+                minimap_info.tile_size,
+                2.0,
+                mq::WHITE,
+            );
+        }
+    }
+    // brush, size and save buttons, at the bottom of the 3D view
+    fn panel(&mut self, map: &mut Map, player: &Player, scaling_info: &ScalingInfo) {
+        let size = mq::Vec2::new(360.0, 140.0);
+        let position = scaling_info.offset
+            + mq::Vec2::new(
+                scaling_info.width * 0.75 - size.x / 2.0,
+                scaling_info.height - size.y - 8.0,
+            );
+        // never shrink past the player or the spawn
+        let min_width = (player.pos.x / TILE_SIZE as f32).max(map.spawn.x) as u32 + 1;
+        let min_height = (player.pos.y / TILE_SIZE as f32).max(map.spawn.y) as u32 + 1;
+
+        widgets::Window::new(hash!(), position, size)
+            .label("Map editor")
+            .movable(false)
+            .ui(&mut root_ui(), |ui| {
+                let brush_names = self
+                    .brush_names
+                    .iter()
+                    .map(String::as_str)
+                    .collect::<Vec<&str>>();
+                ui.combo_box(hash!(), "Brush", &brush_names, &mut self.brush);
+
+                ui.label(None, &format!("Size: {}x{}", map.width, map.height));
+                ui.same_line(0.0);
+                if ui.button(None, "W-") && map.width > min_width {
+                    map.resize(map.width - 1, map.height);
+                }
+                ui.same_line(0.0);
+                if ui.button(None, "W+") {
+                    map.resize(map.width + 1, map.height);
+                }
+                ui.same_line(0.0);
+                if ui.button(None, "H-") && map.height > min_height {
+                    map.resize(map.width, map.height - 1);
+                }
+                ui.same_line(0.0);
+                if ui.button(None, "H+") {
+                    map.resize(map.width, map.height + 1);
+                }
+
+                if ui.button(None, format!("Save to {}", self.save_path).as_str()) {
+                    self.status = match map.save(&self.save_path) {
+                        Ok(()) => "Saved".to_owned(),
+                        Err(err) => format!("Not saved: {}", err),
+                    };
+                }
+                ui.label(None, &self.status);
+                ui.label(None, "Right click to erase, M to close");
+            });
+    }
+}
+
+async fn run(
+    mut map: Map,
+    textures: Textures,
+    resolution: (u32, u32),
+    dynamic_resolution: bool,
+    mut settings: Settings,
+    config_path: String,
+    level_path: String,
+) {
+    let mut player = Player::new(map.spawn * TILE_SIZE as f32, map.spawn_angle);
 
     let mut mouse_grapped = false;
     mq::set_cursor_grab(mouse_grapped);
     mq::show_mouse(!mouse_grapped);
 
-    #[rustfmt::skip]
-    let map = [
-        1, 0, 0, 0, 0, 0, 0, 1,
-        1, 0, 0, 0, 0, 0, 0, 2,
-        2, 0, 0, 0, 0, 0, 0, 3,
-        2, 0, 0, 1, 3, 0, 0, 3,
-        3, 0, 0, 0, 0, 0, 0, 2,
-        3, 0, 0, 3, 0, 2, 0, 1,
-        1, 0, 0, 0, 0, 0, 0, 1,
-        1, 3, 3, 3, 2, 1, 2, 1,
-    ];
-
-    let wall_image = mq::Image::from_file_with_format(
-        include_bytes!("../resources/WolfensteinTextures.png"),
-        Some(mq::ImageFormat::Png),
-    );
+    let mut settings_open = false;
+    let mut saved_settings = settings;
 
-    let mut num_rays = 0.0;
+    let mut editor = Editor::new(&map, &textures, level_path);
+    let mut editor_open = false;
+
+    let mut minimap_mode = MinimapMode::Side;
+    let mut minimap_fullscreen = false;
+    let mut fog_of_war = false;
+    let mut explored = Explored::new(map.width, map.height);
+
+    let mut loaded = 0.0; // fraction of the columns drawn
+
+    let (max_width, max_height) = resolution;
+    let mut dynamic_resolution =
+        dynamic_resolution.then(|| DynamicResolution::new(max_width, max_height, RENDER_BUDGET));
 
     let mut output_image =
-        mq::Image::gen_image_color(WINDOW_WIDTH as u16 / 2, WINDOW_HEIGHT as u16, NORD_COLOR);
-    let output_texture = mq::Texture2D::from_image(&output_image);
+        mq::Image::gen_image_color(max_width as u16, max_height as u16, NORD_COLOR);
+    let mut output_texture = mq::Texture2D::from_image(&output_image);
 
     loop {
         let scaling_info = ScalingInfo::new();
 
-        if mq::is_key_pressed(mq::KeyCode::Tab) || mq::is_key_down(mq::KeyCode::Escape) {
+        if mq::is_key_pressed(mq::KeyCode::Escape) {
+            settings_open = !settings_open;
+            mouse_grapped = false;
+            set_grab(mouse_grapped);
+
+            if !settings_open && settings != saved_settings {
+                // the game goes on with the new settings either way
+                if let Err(err) = settings.save(&config_path) {
+                    eprintln!("failed to save settings: {}", err);
+                }
+                saved_settings = settings;
+            }
+        } else if mq::is_key_pressed(mq::KeyCode::Tab) {
             mouse_grapped = false;
             set_grab(mouse_grapped);
-        } else if mq::is_mouse_button_pressed(mq::MouseButton::Left) {
+        } else if mq::is_key_pressed(mq::KeyCode::M) {
+            editor_open = !editor_open;
+            mouse_grapped = false;
+            set_grab(mouse_grapped);
+        } else if mq::is_mouse_button_pressed(mq::MouseButton::Left)
+            && !settings_open
+            && !editor_open
+        {
             if !mouse_grapped {
                 let (mouse_pos_x, mouse_pos_y) = mq::mouse_position();
                 if mouse_pos_x >= scaling_info.offset.x
@@ -446,138 +834,206 @@ async fn main() {
             }
         }
 
+        if mq::is_key_pressed(mq::KeyCode::N) {
+            minimap_mode = minimap_mode.next();
+        }
+        if mq::is_key_pressed(mq::KeyCode::F) {
+            minimap_fullscreen = !minimap_fullscreen;
+        }
+        if mq::is_key_pressed(mq::KeyCode::G) {
+            fog_of_war = !fog_of_war;
+        }
+        // the editor paints on the whole, unrotated map
+        let minimap_mode = if editor_open {
+            MinimapMode::Side
+        } else {
+            minimap_mode
+        };
+        let minimap_fullscreen = minimap_fullscreen && !editor_open;
+        let fog_of_war = fog_of_war && !editor_open;
+
+        // the 3D view fills the window unless the minimap is next to it
+        let (view_position, view_size) = match minimap_mode {
+            MinimapMode::Side => (
+                scaling_info.offset + mq::Vec2::new(scaling_info.width / 2.0, 0.0),
+                mq::Vec2::new(scaling_info.width / 2.0, scaling_info.height),
+            ),
+            MinimapMode::Corner | MinimapMode::Rotating => (
+                scaling_info.offset,
+                mq::Vec2::new(scaling_info.width, scaling_info.height),
+            ),
+        };
+        let side_minimap_info = MinimapInfo::fit(
+            &map,
+            scaling_info.offset,
+            mq::Vec2::new(scaling_info.width / 2.0, scaling_info.height),
+        );
+
         if mq::is_key_pressed(mq::KeyCode::R) {
-            num_rays = 0.0;
+            loaded = 0.0;
             output_image.get_image_data_mut().fill(NORD_COLOR.into());
         }
 
-        let floor_level =
-            (WINDOW_HEIGHT as f32 / 2.0) * (1.0 + player.angle_vertical.tan() / (FOV / 2.0).tan());
-
         let delta = mq::get_frame_time(); // seconds
 
         mq::clear_background(NORD_COLOR);
 
-        draw_map(&map, &scaling_info);
-
-        player.input(delta, mouse_grapped, &map);
-        player.draw(&scaling_info);
-
-        if num_rays < NUM_RAYS as f32 {
-            num_rays += delta * RAYS_PER_SECOND as f32;
-        } else {
-            num_rays = NUM_RAYS as f32;
+        if editor_open && !settings_open {
+            editor.paint(&mut map, &side_minimap_info, &player);
         }
-        let ray_touches = player.cast_rays(&map, num_rays as u32);
 
-        for (i, ray_touch) in ray_touches.iter().enumerate() {
-            let ray = &ray_touch.0;
-            let ray_hit = &ray_touch.1;
-
-            let x = i as i32;
-
-            if let Some(ray_hit) = ray_hit {
-                let angle_between = player.angle - ray.angle;
-                let z = ray_hit.world_distance * angle_between.cos();
-
-                let projection_dist = (TILE_SIZE as f32 / 2.0) / (FOV / 2.0).tan();
-
-                let h = (WINDOW_HEIGHT as f32 * projection_dist) / z;
-                let y0 = floor_level - (h / 2.0);
-                let y1 = y0 + h;
+        if !settings_open {
+            player.input(delta, mouse_grapped, &map, &settings);
+        }
+        if mq::is_key_pressed(mq::KeyCode::E) {
+            let target = (player.pos + player.direction * TILE_SIZE as f32) / TILE_SIZE as f32;
+            map.use_door(target.x.floor() as i32, target.y.floor() as i32);
+        }
+        map.update_doors(
+            delta,
+            (
+                (player.pos.x / TILE_SIZE as f32) as u32,
+                (player.pos.y / TILE_SIZE as f32) as u32,
+            ),
+        );
 
-                let y0 = y0.round() as i32;
-                let y1 = y1.round() as i32;
+        let (width, height) = dynamic_resolution
+            .as_ref()
+            .map_or(resolution, |dynamic_resolution| {
+                dynamic_resolution.resolution()
+            });
+        if (width as usize, height as usize) != (output_image.width(), output_image.height()) {
+            output_image = mq::Image::gen_image_color(width as u16, height as u16, NORD_COLOR);
+            output_texture.delete();
+            output_texture = mq::Texture2D::from_image(&output_image);
+        }
 
-                let texture_x = (ray_hit.wall_coord * wall_image.width() as f32).round() as i32;
-                let texture_y0 =
-                    (wall_image.height() as i32 / NUM_TEXTURES) * (ray_hit.wall_type as i32 - 1);
-                let texture_y1 = texture_y0 + wall_image.height() as i32 / NUM_TEXTURES;
+        loaded = (loaded + delta / LOADING_TIME).min(1.0);
+        let render_start = mq::get_time();
+        let ray_touches = render::render_frame(
+            &mut output_image,
+            &map,
+            &player.camera(&settings),
+            &textures,
+            (loaded * width as f32) as u32,
+        );
+        if let Some(dynamic_resolution) = &mut dynamic_resolution {
+            dynamic_resolution.update((mq::get_time() - render_start) as f32);
+        }
 
-                let sky = VerticalLine::new(x, 0, y0);
-                vertical_line(sky, &mut output_image, BACKGROUND_COLOR);
+        // the editor can resize the map
+        if (explored.width, explored.height) != (map.width, map.height) {
+            explored = Explored::new(map.width, map.height);
+        }
+        for (ray, ray_hit) in ray_touches.iter() {
+            explored.reveal(ray, ray_hit.as_ref());
+        }
 
-                let fog_brightness = (2.0 * ray_hit.world_distance / VIEW_DISTANCE - 1.0).max(0.0);
+        // pixels are square, so keep the aspect ratio of the render resolution
+        let panel_height = view_size.y + 1.0;
+        let view_scale = (view_size.x / width as f32).min(panel_height / height as f32);
+        let image_size = mq::Vec2::new(width as f32, height as f32) * view_scale;
+        output_texture.update(&output_image);
+        mq::draw_texture_ex(
+            output_texture,
+            view_position.x + (view_size.x - image_size.x) / 2.0,
+            view_position.y + (panel_height - image_size.y) / 2.0,
+            mq::WHITE,
+            mq::DrawTextureParams {
+                dest_size: Some(image_size),
+                ..Default::default()
+            },
+        );
 
-                let wall_line = VerticalLine::new(x, y0, y1);
-                let texture_line = VerticalLine::new(texture_x, texture_y0, texture_y1);
-                vertical_textured_line_with_fog(
-                    wall_line,
-                    &mut output_image,
-                    &wall_image,
-                    texture_line,
-                    fog_brightness,
-                );
+        // on top of the 3D view, except when it's next to it
+        let minimap_info = if minimap_fullscreen {
+            mq::draw_rectangle(
+                scaling_info.offset.x,
+                scaling_info.offset.y,
+                scaling_info.width,
+                scaling_info.height,
+                NORD_COLOR,
+            );
+            MinimapInfo::fit(
+                &map,
+                scaling_info.offset,
+                mq::Vec2::new(scaling_info.width, scaling_info.height),
+            )
+        } else {
+            let size = scaling_info.height * 0.35;
+            let corner = scaling_info.offset + mq::Vec2::new(scaling_info.width - size - 8.0, 8.0);
+            match minimap_mode {
+                MinimapMode::Side => side_minimap_info,
+                MinimapMode::Corner => {
+                    mq::draw_rectangle(corner.x, corner.y, size, size, MINIMAP_BACKGROUND);
+                    MinimapInfo::fit(&map, corner, mq::Vec2::splat(size))
+                }
+                MinimapMode::Rotating => {
+                    let centre = corner + size / 2.0;
+                    mq::draw_circle(centre.x, centre.y, size / 2.0, MINIMAP_BACKGROUND);
+                    MinimapInfo::rotating(&player, centre, size / 2.0)
+                }
+            }
+        };
 
-                let floor = VerticalLine::new(x, y1, WINDOW_HEIGHT as i32);
-                vertical_line(floor, &mut output_image, GROUND_COLOR);
+        draw_map(&map, &minimap_info, fog_of_war.then_some(&explored));
+        if editor_open {
+            editor.draw(&map, &minimap_info);
+        }
 
+        for (_, ray_hit) in ray_touches.iter() {
+            if let Some(ray_hit) = ray_hit {
                 let color = if ray_hit.x_move {
                     WALL_COLOR_LIGHT
                 } else {
                     WALL_COLOR_DARK
                 };
+                let line_start = minimap_info.world_to_screen(player.pos);
+                let mut line_end = minimap_info.world_to_screen(ray_hit.pos);
+                if let Some(radius) = minimap_info.clip_radius {
+                    line_end = minimap_info.origin
+                        + (line_end - minimap_info.origin).clamp_length_max(radius);
+                }
                 mq::draw_line(
-                    scaling_info.offset.x + player.pos.x * scaling_info.width / WINDOW_WIDTH as f32,
-                    scaling_info.offset.y
-                        + player.pos.y * scaling_info.height / WINDOW_HEIGHT as f32,
-                    scaling_info.offset.x
-                        + ray_hit.pos.x * scaling_info.width / WINDOW_WIDTH as f32,
-                    scaling_info.offset.y
-                        + ray_hit.pos.y * scaling_info.height / WINDOW_HEIGHT as f32,
+                    line_start.x,
+                    line_start.y,
+                    line_end.x,
+                    line_end.y,
                     3.0,
                     color,
                 );
-            } else {
-                let floor_y = floor_level.round() as i32;
-
-                let sky = VerticalLine::new(x, 0, floor_y);
-                vertical_line(sky, &mut output_image, BACKGROUND_COLOR);
-
-                let floor = VerticalLine::new(x, floor_y, WINDOW_HEIGHT as i32);
-                vertical_line(floor, &mut output_image, GROUND_COLOR);
             }
         }
 
-        output_texture.update(&output_image);
-        mq::draw_texture_ex(
-            output_texture,
-            scaling_info.offset.x + scaling_info.width / 2.0,
-            scaling_info.offset.y,
-            mq::WHITE,
-            mq::DrawTextureParams {
-                dest_size: Some(mq::Vec2::new(
-                    scaling_info.width / 2.0,
-                    scaling_info.height + 1.0,
-                )),
-                ..Default::default()
-            },
-        );
+        player.draw(&minimap_info);
 
         // crosshair
-        mq::draw_line(
-            scaling_info.offset.x + scaling_info.width * (3.0 / 4.0) - 10.0,
-            scaling_info.offset.y + scaling_info.height / 2.0,
-            scaling_info.offset.x + scaling_info.width * (3.0 / 4.0) + 10.0,
-            scaling_info.offset.y + scaling_info.height / 2.0,
-            2.0,
-            mq::BLACK,
-        );
-        mq::draw_line(
-            scaling_info.offset.x + scaling_info.width * (3.0 / 4.0),
-            scaling_info.offset.y + scaling_info.height / 2.0 - 10.0,
-            scaling_info.offset.x + scaling_info.width * (3.0 / 4.0),
-            scaling_info.offset.y + scaling_info.height / 2.0 + 10.0,
-            2.0,
-            mq::BLACK,
-        );
+        if !minimap_fullscreen {
+            let centre = view_position + view_size / 2.0;
+            mq::draw_line(
+                centre.x - 10.0,
+                centre.y,
+                centre.x + 10.0,
+                centre.y,
+                2.0,
+                mq::BLACK,
+            );
+            mq::draw_line(
+                centre.x,
+                centre.y - 10.0,
+                centre.x,
+                centre.y + 10.0,
+                2.0,
+                mq::BLACK,
+            );
+        }
 
         // text background
         mq::draw_rectangle(
             scaling_info.offset.x + 1.0,
             scaling_info.offset.y + 1.0,
             140.0,
-            35.0,
+            65.0,
             mq::Color::new(1.0, 1.0, 1.0, 1.0),
         );
 
@@ -596,6 +1052,27 @@ async fn main() {
             20.,
             mq::BLUE,
         );
+        mq::draw_text(
+            format!("MAP: {}", map.name).as_str(),
+            scaling_info.offset.x + 5.,
+            scaling_info.offset.y + 45.,
+            20.,
+            mq::BLUE,
+        );
+        mq::draw_text(
+            format!("RES: {}x{}", width, height).as_str(),
+            scaling_info.offset.x + 5.,
+            scaling_info.offset.y + 60.,
+            20.,
+            mq::BLUE,
+        );
+
+        if editor_open {
+            editor.panel(&mut map, &player, &scaling_info);
+        }
+        if settings_open {
+            settings_menu(&mut settings, &scaling_info);
+        }
 
         mq::next_frame().await
     }
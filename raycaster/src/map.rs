@@ -0,0 +1,749 @@
+use macroquad::prelude as mq;
+
+use std::fmt;
+
+use crate::fog::{Falloff, Fog};
+use crate::render::Textures;
+use crate::{DOOR_OPEN_TIME, DOOR_SPEED, TILE_SIZE};
+
+/*
+    Level file format:
+
+    # comments start with a '#'
+    name: Default      # can't contain a '#'
+    spawn: 4.5 4.5 0   # x y (in tiles) and angle (in degrees)
+    floor: 1           # optional, texture from the floor atlas
+    ceiling: wall 2    # optional, texture from the wall atlas
+    sky: 1             # optional, texture from the sky atlas
+    fog: exp 2 8       # optional, linear, exp or exp2 and start and end distance (in tiles)
+    fog_color: 73 255 255  # optional, red green blue (0 to 255)
+    border: 1          # optional, tile id of everything outside of the grid
+    sprite: 1.5 1.5 1  # x y (in tiles) and texture from the sprite atlas, can be repeated
+    tile: 4 1 1 2 2    # id and textures for the north, south, east and west faces
+    tile: 5 3 3 3 3 0.5  # optional height in tiles, 1 by default
+    tiles:
+    1 0 0 1
+    1 0 0 2
+    1 1 D 1
+
+    Everything after `tiles:` is the grid, one row per line.
+    0 = no wall, D = door, any other id is a wall that uses the texture with the
+    same number on every face unless it was defined with `tile:` before.
+    Texture numbers are the order of the textures in the atlases (starting at 1).
+    Doors slide into the walls on either side of them.
+    Walls can be lower (ledges) or taller (pillars) than a tile, up to
+    MAX_WALL_HEIGHT tiles, doors are always one tile tall.
+    A floor or ceiling without a texture is drawn as a flat color, or the sky
+    instead of the ceiling if the level has one.
+    The outside of the map is always solid, without a border it isn't drawn.
+*/
+
+#[derive(Debug)]
+pub enum MapError {
+    Io(std::io::Error),
+    Syntax {
+        line: usize,
+    },
+    UnknownKey {
+        line: usize,
+        key: String,
+    },
+    InvalidValue {
+        line: usize,
+        key: String,
+    },
+    UnknownTile {
+        line: usize,
+        id: String,
+    },
+    RaggedRow {
+        line: usize,
+        expected: usize,
+        found: usize,
+    },
+    MissingTiles,
+    MissingSpawn,
+    SpawnOutOfBounds,
+    SpawnInWall,
+    InvalidName,
+    UnknownTexture {
+        atlas: &'static str,
+        texture: u8,
+    },
+}
+impl fmt::Display for MapError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MapError::Io(err) => write!(f, "could not read level file: {}", err),
+            MapError::Syntax { line } => {
+                write!(f, "line {}: expected `key: value` or `tiles:`", line)
+            }
+            MapError::UnknownKey { line, key } => write!(f, "line {}: unknown key `{}`", line, key),
+            MapError::InvalidValue { line, key } => {
+                write!(f, "line {}: invalid value for `{}`", line, key)
+            }
+            MapError::UnknownTile { line, id } => write!(
+                f,
+                "line {}: unknown tile id `{}` (expected 0 to {} or D)",
+                line,
+                id,
+                DOOR - 1
+            ),
+            MapError::RaggedRow {
+                line,
+                expected,
+                found,
+            } => write!(
+                f,
+                "line {}: row has {} tiles but the first row has {}",
+                line, found, expected
+            ),
+            MapError::MissingTiles => write!(f, "level has no `tiles:` grid"),
+            MapError::MissingSpawn => write!(f, "level has no `spawn:`"),
+            MapError::SpawnOutOfBounds => write!(f, "spawn is outside of the map"),
+            MapError::SpawnInWall => write!(f, "spawn is inside a wall"),
+            MapError::InvalidName => write!(f, "name can't contain a `#` or a line break"),
+            MapError::UnknownTexture { atlas, texture } => {
+                write!(f, "texture {} is not in the {} atlas", texture, atlas)
+            }
+        }
+    }
+}
+impl std::error::Error for MapError {}
+impl From<std::io::Error> for MapError {
+    fn from(err: std::io::Error) -> Self {
+        MapError::Io(err)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum Surface {
+    Flat,
+    Wall(u8),  // texture from the wall atlas
+    Floor(u8), // texture from the floor atlas
+}
+
+pub const DOOR: u8 = u8::MAX;
+
+pub const MAX_WALL_HEIGHT: f32 = 4.0; // in tiles
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Face {
+    North, // facing -y
+    South, // facing +y
+    East,  // facing +x
+    West,  // facing -x
+}
+
+// textures for each face of a wall, and its height
+// the top of walls lower than the camera uses the north texture
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TileType {
+    pub north: u8,
+    pub south: u8,
+    pub east: u8,
+    pub west: u8,
+    pub height: f32, // in tiles
+}
+impl TileType {
+    pub fn uniform(texture: u8) -> Self {
+        Self {
+            north: texture,
+            south: texture,
+            east: texture,
+            west: texture,
+            height: 1.0,
+        }
+    }
+    pub fn texture(&self, face: Face) -> u8 {
+        match face {
+            Face::North => self.north,
+            Face::South => self.south,
+            Face::East => self.east,
+            Face::West => self.west,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Door {
+    pub x: u32,
+    pub y: u32,
+    pub x_aligned: bool, // door runs along the x axis (walls to the left and right)
+    pub open_amount: f32, // 0.0 = closed, 1.0 = open
+    pub opening: bool,
+    pub open_timer: f32, // seconds until it closes again
+}
+
+#[derive(Debug)]
+pub struct Sprite {
+    pub pos: mq::Vec2, // in tiles
+    pub texture: u8,
+}
+
+#[derive(Debug)]
+pub struct Map {
+    pub name: String,
+    pub width: u32,
+    pub height: u32,
+    pub tiles: Vec<u8>,
+    pub tile_types: Vec<Option<TileType>>, // indexed by tile id
+    pub border: u8,                        // tile outside of the map, 0 = none
+    pub spawn: mq::Vec2,                   // in tiles
+    pub spawn_angle: f32,                  // in radians
+    pub floor: Surface,
+    pub ceiling: Surface,
+    pub sky: Option<u8>, // drawn where the ceiling is flat
+    pub fog: Fog,
+    pub sprites: Vec<Sprite>,
+    pub doors: Vec<Door>,
+}
+impl Map {
+    pub fn load(path: &str) -> Result<Self, MapError> {
+        let source = std::fs::read_to_string(path)?;
+        Self::parse(&source)
+    }
+    pub fn parse(source: &str) -> Result<Self, MapError> {
+        let mut name = String::new();
+        let mut spawn = None;
+        let mut floor = Surface::Flat;
+        let mut ceiling = Surface::Flat;
+        let mut sky = None;
+        let mut fog = Fog::default();
+        let mut border = 0;
+        let mut sprites = Vec::new();
+        let mut tile_types = vec![None; DOOR as usize];
+        let mut rows: Vec<Vec<u8>> = Vec::new();
+        let mut in_tiles = false;
+
+        for (i, line) in source.lines().enumerate() {
+            let line_number = i + 1;
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            if in_tiles {
+                let row = line
+                    .split_whitespace()
+                    .map(|id| parse_tile(id, line_number, &mut tile_types))
+                    .collect::<Result<Vec<u8>, MapError>>()?;
+                if let Some(first_row) = rows.first() {
+                    if row.len() != first_row.len() {
+                        return Err(MapError::RaggedRow {
+                            line: line_number,
+                            expected: first_row.len(),
+                            found: row.len(),
+                        });
+                    }
+                }
+                rows.push(row);
+                continue;
+            }
+
+            let (key, value) = line
+                .split_once(':')
+                .ok_or(MapError::Syntax { line: line_number })?;
+            let (key, value) = (key.trim(), value.trim());
+            match key {
+                "name" => name = value.to_owned(),
+                "spawn" => spawn = Some(parse_spawn(value, line_number)?),
+                "floor" => floor = parse_surface(value, line_number, key)?,
+                "ceiling" => ceiling = parse_surface(value, line_number, key)?,
+                "sky" => match value.parse::<u8>() {
+                    Ok(id) if id != 0 => sky = Some(id),
+                    _ => {
+                        return Err(MapError::InvalidValue {
+                            line: line_number,
+                            key: key.to_owned(),
+                        })
+                    }
+                },
+                "fog" => (fog.falloff, fog.start, fog.end) = parse_fog(value, line_number)?,
+                "fog_color" => fog.color = parse_color(value, line_number, key)?,
+                "sprite" => sprites.push(parse_sprite(value, line_number)?),
+                "border" => {
+                    border = parse_tile(value, line_number, &mut tile_types)?;
+                    if border == DOOR {
+                        return Err(MapError::InvalidValue {
+                            line: line_number,
+                            key: key.to_owned(),
+                        });
+                    }
+                }
+                "tile" => {
+                    let (id, tile_type) = parse_tile_type(value, line_number)?;
+                    tile_types[id as usize] = Some(tile_type);
+                }
+                "tiles" => in_tiles = true,
+                _ => {
+                    return Err(MapError::UnknownKey {
+                        line: line_number,
+                        key: key.to_owned(),
+                    })
+                }
+            }
+        }
+
+        if rows.is_empty() {
+            return Err(MapError::MissingTiles);
+        }
+        let (spawn, spawn_angle) = spawn.ok_or(MapError::MissingSpawn)?;
+
+        let mut map = Self {
+            name,
+            width: rows[0].len() as u32,
+            height: rows.len() as u32,
+            tiles: rows.concat(),
+            tile_types,
+            border,
+            spawn,
+            spawn_angle,
+            floor,
+            ceiling,
+            sky,
+            fog,
+            sprites,
+            doors: Vec::new(),
+        };
+
+        map.find_doors();
+        map.check_spawn()?;
+
+        Ok(map)
+    }
+    pub fn save(&self, path: &str) -> Result<(), MapError> {
+        // never write a level that can't be loaded again
+        self.check_spawn()?;
+        // the rest of the line would load as a comment or another key
+        if self.name.contains(['#', '\n', '\r']) {
+            return Err(MapError::InvalidName);
+        }
+        std::fs::write(path, self.to_string())?;
+        Ok(())
+    }
+    fn check_spawn(&self) -> Result<(), MapError> {
+        let spawn = self.spawn;
+        // also false for nan
+        if !(0.0..self.width as f32).contains(&spawn.x)
+            || !(0.0..self.height as f32).contains(&spawn.y)
+        {
+            return Err(MapError::SpawnOutOfBounds);
+        }
+        if self.tile(spawn.x as i32, spawn.y as i32) != 0 {
+            return Err(MapError::SpawnInWall);
+        }
+        Ok(())
+    }
+    // doors that are still in the same place keep their state
+    fn find_doors(&mut self) {
+        let mut doors = Vec::new();
+        for y in 0..self.height {
+            for x in 0..self.width {
+                if self.get(x as i32, y as i32) == Some(DOOR) {
+                    let closed = self.closed_door(x, y);
+                    let door = match self.door(x, y) {
+                        Some(door) => Door {
+                            x_aligned: closed.x_aligned,
+                            ..*door
+                        },
+                        None => closed,
+                    };
+                    doors.push(door);
+                }
+            }
+        }
+        self.doors = doors;
+    }
+    // None outside of the map
+    pub fn get(&self, x: i32, y: i32) -> Option<u8> {
+        if x < 0 || y < 0 || x as u32 >= self.width || y as u32 >= self.height {
+            return None;
+        }
+        Some(self.tiles[(y as u32 * self.width + x as u32) as usize])
+    }
+    // the border tile outside of the map
+    pub fn tile(&self, x: i32, y: i32) -> u8 {
+        self.get(x, y).unwrap_or(self.border)
+    }
+    // ids without a tile type use the texture with the same number on every face
+    pub fn set_tile(&mut self, x: u32, y: u32, id: u8) {
+        if id != 0 && id != DOOR {
+            self.tile_types[id as usize].get_or_insert(TileType::uniform(id));
+        }
+        self.tiles[(y * self.width + x) as usize] = id;
+        self.find_doors();
+    }
+    // keeps the top left corner, new tiles are empty and sprites that end up
+    // outside of the map are removed
+    pub fn resize(&mut self, width: u32, height: u32) {
+        let (width, height) = (width.max(1), height.max(1));
+        let mut tiles = vec![0; (width * height) as usize];
+        for y in 0..height.min(self.height) {
+            for x in 0..width.min(self.width) {
+                tiles[(y * width + x) as usize] = self.tiles[(y * self.width + x) as usize];
+            }
+        }
+        self.tiles = tiles;
+        self.width = width;
+        self.height = height;
+        self.sprites
+            .retain(|sprite| sprite.pos.x < width as f32 && sprite.pos.y < height as f32);
+        self.find_doors();
+    }
+    // texture numbers can only be checked once the atlases are loaded
+    pub fn check_textures(&self, textures: &Textures) -> Result<(), MapError> {
+        let check = |atlas: &'static str, contains: bool, texture: u8| {
+            if contains {
+                Ok(())
+            } else {
+                Err(MapError::UnknownTexture { atlas, texture })
+            }
+        };
+        for tile_type in self.tile_types.iter().flatten() {
+            for texture in [
+                tile_type.north,
+                tile_type.south,
+                tile_type.east,
+                tile_type.west,
+            ] {
+                check("wall", textures.wall.contains(texture), texture)?;
+            }
+        }
+        for surface in [self.floor, self.ceiling] {
+            match surface {
+                Surface::Flat => {}
+                Surface::Wall(texture) => check("wall", textures.wall.contains(texture), texture)?,
+                Surface::Floor(texture) => {
+                    check("floor", textures.floor.contains(texture), texture)?
+                }
+            }
+        }
+        if let Some(texture) = self.sky {
+            check("sky", textures.sky.contains(texture), texture)?;
+        }
+        for sprite in self.sprites.iter() {
+            check(
+                "sprite",
+                textures.sprite.contains(sprite.texture),
+                sprite.texture,
+            )?;
+        }
+        Ok(())
+    }
+    pub fn tile_type(&self, id: u8) -> TileType {
+        self.tile_types[id as usize].expect("tiles are checked when parsing")
+    }
+    // in tiles, doors are always a tile tall
+    pub fn wall_height(&self, id: u8) -> f32 {
+        match id {
+            0 => 0.0,
+            DOOR => 1.0,
+            _ => self.tile_type(id).height,
+        }
+    }
+    pub fn max_wall_height(&self) -> f32 {
+        self.tile_types
+            .iter()
+            .flatten()
+            .map(|tile_type| tile_type.height)
+            .fold(1.0, f32::max)
+    }
+    pub fn door(&self, x: u32, y: u32) -> Option<&Door> {
+        self.doors.iter().find(|door| door.x == x && door.y == y)
+    }
+    // the door at x, y, or a closed one if tiles has a door that doors doesn't
+    // (tiles was changed without set_tile)
+    pub fn door_or_closed(&self, x: u32, y: u32) -> Door {
+        self.door(x, y)
+            .copied()
+            .unwrap_or_else(|| self.closed_door(x, y))
+    }
+    // runs along the x axis if there are walls to the left and right of it
+    fn closed_door(&self, x: u32, y: u32) -> Door {
+        let is_wall = |x: i32, y: i32| self.get(x, y).is_some_and(|tile| !matches!(tile, 0 | DOOR));
+        Door {
+            x,
+            y,
+            x_aligned: is_wall(x as i32 - 1, y as i32) && is_wall(x as i32 + 1, y as i32),
+            open_amount: 0.0,
+            opening: false,
+            open_timer: 0.0,
+        }
+    }
+    // doors only stop being solid once they are (almost) fully open, the
+    // outside of the map is always solid, even without a border
+    pub fn is_solid(&self, x: i32, y: i32) -> bool {
+        match self.get(x, y) {
+            None => true,
+            Some(0) => false,
+            Some(DOOR) => self
+                .door(x as u32, y as u32)
+                .is_none_or(|door| door.open_amount < 0.9),
+            Some(_) => true,
+        }
+    }
+    pub fn use_door(&mut self, x: i32, y: i32) {
+        if let Some(door) = self
+            .doors
+            .iter_mut()
+            .find(|door| (door.x as i32, door.y as i32) == (x, y))
+        {
+            door.opening = !door.opening;
+            door.open_timer = DOOR_OPEN_TIME;
+        }
+    }
+    pub fn update_doors(&mut self, delta: f32, player_tile: (u32, u32)) {
+        for door in self.doors.iter_mut() {
+            if door.opening {
+                door.open_amount = (door.open_amount + DOOR_SPEED * delta).min(1.0);
+                door.open_timer -= delta;
+                // never close on top of the player
+                if door.open_timer <= 0.0 && player_tile != (door.x, door.y) {
+                    door.opening = false;
+                }
+            } else {
+                door.open_amount = (door.open_amount - DOOR_SPEED * delta).max(0.0);
+            }
+        }
+    }
+}
+
+// in the level file format
+impl fmt::Display for Map {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if !self.name.is_empty() {
+            writeln!(f, "name: {}", self.name)?;
+        }
+        writeln!(
+            f,
+            "spawn: {} {} {}",
+            self.spawn.x,
+            self.spawn.y,
+            self.spawn_angle.to_degrees()
+        )?;
+        for (key, surface) in [("floor", self.floor), ("ceiling", self.ceiling)] {
+            match surface {
+                Surface::Flat => {}
+                Surface::Wall(texture) => writeln!(f, "{}: wall {}", key, texture)?,
+                Surface::Floor(texture) => writeln!(f, "{}: {}", key, texture)?,
+            }
+        }
+        if let Some(texture) = self.sky {
+            writeln!(f, "sky: {}", texture)?;
+        }
+        let default_fog = Fog::default();
+        if (self.fog.falloff, self.fog.start, self.fog.end)
+            != (default_fog.falloff, default_fog.start, default_fog.end)
+        {
+            writeln!(
+                f,
+                "fog: {} {} {}",
+                self.fog.falloff,
+                self.fog.start / TILE_SIZE as f32,
+                self.fog.end / TILE_SIZE as f32
+            )?;
+        }
+        if self.fog.color != default_fog.color {
+            let channel = |value: f32| (value * 255.0).round() as u8;
+            let color = self.fog.color;
+            writeln!(
+                f,
+                "fog_color: {} {} {}",
+                channel(color.r),
+                channel(color.g),
+                channel(color.b)
+            )?;
+        }
+        for sprite in self.sprites.iter() {
+            writeln!(
+                f,
+                "sprite: {} {} {}",
+                sprite.pos.x, sprite.pos.y, sprite.texture
+            )?;
+        }
+        if self.border != 0 {
+            writeln!(f, "border: {}", self.border)?;
+        }
+        // uniform tiles don't need to be defined
+        for (id, tile_type) in self.tile_types.iter().enumerate() {
+            match tile_type {
+                Some(tile_type) if *tile_type != TileType::uniform(id as u8) => {
+                    write!(
+                        f,
+                        "tile: {} {} {} {} {}",
+                        id, tile_type.north, tile_type.south, tile_type.east, tile_type.west
+                    )?;
+                    if tile_type.height != 1.0 {
+                        write!(f, " {}", tile_type.height)?;
+                    }
+                    writeln!(f)?;
+                }
+                _ => {}
+            }
+        }
+        writeln!(f, "tiles:")?;
+        for row in self.tiles.chunks(self.width as usize) {
+            let row = row
+                .iter()
+                .map(|&id| match id {
+                    DOOR => "D".to_owned(),
+                    _ => id.to_string(),
+                })
+                .collect::<Vec<String>>();
+            writeln!(f, "{}", row.join(" "))?;
+        }
+        Ok(())
+    }
+}
+
+fn parse_tile(id: &str, line: usize, tile_types: &mut [Option<TileType>]) -> Result<u8, MapError> {
+    if id == "D" {
+        return Ok(DOOR);
+    }
+    match id.parse::<u8>() {
+        Ok(0) => Ok(0),
+        Ok(tile) if tile != DOOR => {
+            tile_types[tile as usize].get_or_insert(TileType::uniform(tile));
+            Ok(tile)
+        }
+        _ => Err(MapError::UnknownTile {
+            line,
+            id: id.to_owned(),
+        }),
+    }
+}
+
+fn parse_spawn(value: &str, line: usize) -> Result<(mq::Vec2, f32), MapError> {
+    let invalid = || MapError::InvalidValue {
+        line,
+        key: "spawn".to_owned(),
+    };
+    let numbers = value
+        .split_whitespace()
+        .map(|n| {
+            n.parse::<f32>()
+                .ok()
+                .filter(|n| n.is_finite())
+                .ok_or_else(invalid)
+        })
+        .collect::<Result<Vec<f32>, MapError>>()?;
+    match numbers[..] {
+        [x, y, angle] => Ok((mq::Vec2::new(x, y), angle.to_radians())),
+        _ => Err(invalid()),
+    }
+}
+
+fn parse_surface(value: &str, line: usize, key: &str) -> Result<Surface, MapError> {
+    let invalid = || MapError::InvalidValue {
+        line,
+        key: key.to_owned(),
+    };
+    let (atlas, id) = match value.split_once(' ') {
+        Some((atlas, id)) => (atlas, id.trim()),
+        None => ("floor", value),
+    };
+    let id = id.parse::<u8>().map_err(|_| invalid())?;
+    match atlas {
+        "wall" if id != 0 => Ok(Surface::Wall(id)),
+        "floor" if id != 0 => Ok(Surface::Floor(id)),
+        _ => Err(invalid()),
+    }
+}
+
+// falloff, start and end (in pixels)
+fn parse_fog(value: &str, line: usize) -> Result<(Falloff, f32, f32), MapError> {
+    let invalid = || MapError::InvalidValue {
+        line,
+        key: "fog".to_owned(),
+    };
+    match value.split_whitespace().collect::<Vec<&str>>()[..] {
+        [falloff, start, end] => {
+            let falloff = Falloff::parse(falloff).ok_or_else(invalid)?;
+            let parse = |n: &str| n.parse::<f32>().ok().filter(|n| n.is_finite());
+            let start = parse(start).ok_or_else(invalid)?;
+            let end = parse(end).ok_or_else(invalid)?;
+            if start < 0.0 || end <= start {
+                return Err(invalid());
+            }
+            Ok((falloff, start * TILE_SIZE as f32, end * TILE_SIZE as f32))
+        }
+        _ => Err(invalid()),
+    }
+}
+
+fn parse_color(value: &str, line: usize, key: &str) -> Result<mq::Color, MapError> {
+    let invalid = || MapError::InvalidValue {
+        line,
+        key: key.to_owned(),
+    };
+    match value
+        .split_whitespace()
+        .map(|channel| channel.parse::<u8>())
+        .collect::<Result<Vec<u8>, _>>()
+        .map_err(|_| invalid())?[..]
+    {
+        [r, g, b] => Ok(mq::Color::from_rgba(r, g, b, 255)),
+        _ => Err(invalid()),
+    }
+}
+
+fn parse_sprite(value: &str, line: usize) -> Result<Sprite, MapError> {
+    let invalid = || MapError::InvalidValue {
+        line,
+        key: "sprite".to_owned(),
+    };
+    match value.split_whitespace().collect::<Vec<&str>>()[..] {
+        [x, y, texture] => {
+            let parse = |n: &str| n.parse::<f32>().ok().filter(|n| n.is_finite());
+            let x = parse(x).ok_or_else(invalid)?;
+            let y = parse(y).ok_or_else(invalid)?;
+            let texture = texture.parse::<u8>().map_err(|_| invalid())?;
+            if texture == 0 {
+                return Err(invalid());
+            }
+            Ok(Sprite {
+                pos: mq::Vec2::new(x, y),
+                texture,
+            })
+        }
+        _ => Err(invalid()),
+    }
+}
+
+fn parse_tile_type(value: &str, line: usize) -> Result<(u8, TileType), MapError> {
+    let invalid = || MapError::InvalidValue {
+        line,
+        key: "tile".to_owned(),
+    };
+    let values = value.split_whitespace().collect::<Vec<&str>>();
+    let (textures, height) = match values[..] {
+        [_, _, _, _, _] => (&values[..], 1.0),
+        [_, _, _, _, _, height] => (&values[..5], height.parse::<f32>().map_err(|_| invalid())?),
+        _ => return Err(invalid()),
+    };
+    let numbers = textures
+        .iter()
+        .map(|n| n.parse::<u8>().map_err(|_| invalid()))
+        .collect::<Result<Vec<u8>, MapError>>()?;
+    let [id, north, south, east, west] = numbers[..] else {
+        return Err(invalid());
+    };
+    if id == 0
+        || id == DOOR
+        || [north, south, east, west].contains(&0)
+        || !(height > 0.0 && height <= MAX_WALL_HEIGHT)
+    {
+        return Err(invalid());
+    }
+    Ok((
+        id,
+        TileType {
+            north,
+            south,
+            east,
+            west,
+            height,
+        },
+    ))
+}
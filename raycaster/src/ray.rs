@@ -0,0 +1,239 @@
+use macroquad::prelude as mq;
+
+use crate::map::{Face, Map, DOOR};
+use crate::TILE_SIZE;
+
+pub struct RayHit {
+    pub pos: mq::Vec2,
+    pub world_distance: f32,
+    pub x_move: bool,
+    pub face: Face,      // side of the tile that was hit
+    pub wall_coord: f32, // 0-1.0 as x
+    pub wall_type: u8,
+    pub tile: (i32, i32),   // outside of the map for the border
+    pub door_jamb: bool,    // wall next to a door
+    pub exit_pos: mq::Vec2, // where the ray leaves the tile
+}
+#[derive(Clone, Copy)]
+pub struct Ray {
+    pub pos: mq::Vec2,
+    pub angle: f32,
+    pub direction: mq::Vec2,
+}
+impl Ray {
+    pub fn new(pos: mq::Vec2, direction: mq::Vec2) -> Self {
+        Self {
+            pos,
+            angle: direction.y.atan2(direction.x),
+            direction,
+        }
+    }
+    // the first wall the ray hits
+    pub fn cast_ray(&self, map: &Map) -> (Ray, Option<RayHit>) {
+        let mut first_hit = None;
+        self.walk(map, |hit| {
+            first_hit = Some(hit);
+            true
+        });
+        (*self, first_hit)
+    }
+    // every wall the ray hits, front to back, eye_height is the camera's height
+    // above the floor (in pixels)
+    // a wall as tall as the tallest one in the map hides everything behind it
+    // unless it is below the eye, so the ray only stops there
+    pub fn cast_ray_through(&self, map: &Map, eye_height: f32) -> (Ray, Vec<RayHit>) {
+        let max_height = map.max_wall_height();
+        let mut hits = Vec::new();
+        self.walk(map, |hit| {
+            let height = map.wall_height(hit.wall_type);
+            hits.push(hit);
+            height >= max_height && height * TILE_SIZE as f32 > eye_height
+        });
+        (*self, hits)
+    }
+    // calls on_hit for every wall until it returns true
+    fn walk(&self, map: &Map, mut on_hit: impl FnMut(RayHit) -> bool) {
+        // in tiles, (0.0, map.width) and (0.0, map.height) inside the map
+        let ray_start = self.pos / TILE_SIZE as f32;
+        let (x, y) = (ray_start.x, ray_start.y);
+
+        let ray_dir = self.direction.normalize_or_zero();
+        if ray_dir == mq::Vec2::ZERO {
+            return;
+        }
+        let grid = GridWalk::new(ray_start, ray_dir);
+        let step = grid.step;
+
+        // nothing can be hit once the ray is further than the map's diagonal
+        let max_distance = ((map.width.pow(2) + map.height.pow(2)) as f32).sqrt() + 1.0;
+        for cell in grid {
+            let GridStep {
+                tile: map_check,
+                distance,
+                exit_distance,
+                x_move,
+            } = cell;
+
+            // outside of the map this is the border tile
+            let wall_type = map.tile(map_check.x as i32, map_check.y as i32);
+            if wall_type == DOOR {
+                let door = map.door_or_closed(map_check.x as u32, map_check.y as u32);
+
+                // the door is inset to the middle of the tile
+                let door_distance = if door.x_aligned {
+                    (map_check.y + 0.5 - y) / ray_dir.y
+                } else {
+                    (map_check.x + 0.5 - x) / ray_dir.x
+                };
+
+                // has to be hit before the ray leaves the tile
+                if door_distance >= distance && door_distance < exit_distance {
+                    let map_pos = ray_start + ray_dir * door_distance;
+                    let wall_pos = map_pos - map_check;
+                    let door_coord = if door.x_aligned {
+                        wall_pos.x
+                    } else {
+                        wall_pos.y
+                    };
+
+                    // the open part of the door slides into the wall
+                    if door_coord >= door.open_amount {
+                        let hit = RayHit {
+                            pos: map_pos * TILE_SIZE as f32,
+                            world_distance: door_distance * TILE_SIZE as f32,
+                            x_move: !door.x_aligned,
+                            face: hit_face(!door.x_aligned, step),
+                            wall_coord: door_coord - door.open_amount,
+                            wall_type,
+                            tile: (map_check.x as i32, map_check.y as i32),
+                            door_jamb: false,
+                            exit_pos: map_pos * TILE_SIZE as f32,
+                        };
+                        if on_hit(hit) {
+                            return;
+                        }
+                    }
+                }
+            } else if wall_type != 0 {
+                // 0 = no wall
+                let pos = self.pos + (ray_dir * distance * TILE_SIZE as f32);
+
+                let map_pos = pos / TILE_SIZE as f32;
+                let wall_pos = map_pos - map_pos.floor();
+                let wall_coord = if x_move { wall_pos.y } else { wall_pos.x };
+
+                let previous_tile = if x_move {
+                    map_check - mq::Vec2::new(step.x, 0.0)
+                } else {
+                    map_check - mq::Vec2::new(0.0, step.y)
+                };
+                let door_jamb = map.tile(previous_tile.x as i32, previous_tile.y as i32) == DOOR;
+
+                let hit = RayHit {
+                    pos,
+                    world_distance: distance * TILE_SIZE as f32,
+                    x_move,
+                    face: hit_face(x_move, step),
+                    wall_coord,
+                    wall_type,
+                    tile: (map_check.x as i32, map_check.y as i32),
+                    door_jamb,
+                    // where the ray leaves the tile, for the top of the wall
+                    exit_pos: self.pos + ray_dir * exit_distance * TILE_SIZE as f32,
+                };
+                if on_hit(hit) {
+                    return;
+                }
+            }
+
+            if distance >= max_distance {
+                return;
+            }
+        }
+    }
+}
+
+// a tile a GridWalk goes through, distances are along the ray (in tiles)
+pub struct GridStep {
+    pub tile: mq::Vec2, // whole numbers
+    pub distance: f32,  // where the ray enters the tile
+    pub exit_distance: f32,
+    pub x_move: bool, // entered through an edge along y, by stepping in x
+}
+
+// every tile a ray goes through after the one it starts in, in order, forever
+// DDA algorithm, shared by the raycaster and anything else that walks the grid
+pub struct GridWalk {
+    pub step: mq::Vec2, // -1.0 or 1.0 on each axis
+    tile: mq::Vec2,
+    // distance along the ray to the next edge along x/y
+    next_edge: mq::Vec2,
+    // distance along the ray to cross a whole tile in x/y
+    step_size: mq::Vec2,
+}
+impl GridWalk {
+    // start in tiles, direction is normalized and not zero
+    pub fn new(start: mq::Vec2, direction: mq::Vec2) -> Self {
+        // 1.0 / 0.0 = infinity, so axis aligned rays never step along the other axis
+        let step_size = mq::Vec2::new(1.0 / direction.x.abs(), 1.0 / direction.y.abs());
+        let tile = start.floor();
+        let mut step = mq::Vec2::ZERO;
+        let mut next_edge = mq::Vec2::ZERO;
+
+        if direction.x < 0.0 {
+            step.x = -1.0;
+            next_edge.x = (start.x - tile.x) * step_size.x;
+        } else {
+            step.x = 1.0;
+            next_edge.x = (tile.x + 1.0 - start.x) * step_size.x;
+        }
+
+        if direction.y < 0.0 {
+            step.y = -1.0;
+            next_edge.y = (start.y - tile.y) * step_size.y;
+        } else {
+            step.y = 1.0;
+            next_edge.y = (tile.y + 1.0 - start.y) * step_size.y;
+        }
+
+        Self {
+            step,
+            tile,
+            next_edge,
+            step_size,
+        }
+    }
+}
+impl Iterator for GridWalk {
+    type Item = GridStep;
+    fn next(&mut self) -> Option<GridStep> {
+        let x_move = self.next_edge.x < self.next_edge.y;
+        let distance = if x_move {
+            self.tile.x += self.step.x;
+            let distance = self.next_edge.x;
+            self.next_edge.x += self.step_size.x;
+            distance
+        } else {
+            self.tile.y += self.step.y;
+            let distance = self.next_edge.y;
+            self.next_edge.y += self.step_size.y;
+            distance
+        };
+        Some(GridStep {
+            tile: self.tile,
+            distance,
+            exit_distance: self.next_edge.x.min(self.next_edge.y),
+            x_move,
+        })
+    }
+}
+
+// moving in +x hits the west face of the next tile, etc
+fn hit_face(x_move: bool, step: mq::Vec2) -> Face {
+    match (x_move, step.x > 0.0, step.y > 0.0) {
+        (true, true, _) => Face::West,
+        (true, false, _) => Face::East,
+        (false, _, true) => Face::North,
+        (false, _, false) => Face::South,
+    }
+}
@@ -0,0 +1,741 @@
+use macroquad::prelude as mq;
+
+use crate::atlas::Atlas;
+use crate::blit::{self, to_fixed, FogTable, TextureColumn};
+use crate::map::{Map, Sprite, Surface, DOOR};
+use crate::ray::{Ray, RayHit};
+use crate::{BACKGROUND_COLOR, GROUND_COLOR, SPRITE_TRANSPARENT_COLOR, TILE_SIZE};
+
+#[derive(Clone, Copy)]
+pub struct Camera {
+    pub pos: mq::Vec2,
+    pub angle: f32,          // in radians
+    pub angle_vertical: f32, // in radians, up is positive, less than 90 degrees
+    pub fov: f32,            // horizontal, in radians
+    pub height: f32,         // above the floor, in pixels (less than a tile)
+}
+// the screen is a plane one unit in front of the camera, spanning
+// direction - plane to direction + plane, so the horizontal FOV is exactly fov
+// pixels are square, so the vertical FOV depends on the aspect ratio
+//
+// looking up and down shears the view instead of rotating it: walls stay
+// vertical and everything moves down by the same number of pixels, so the
+// horizon is the only row that depends on angle_vertical
+impl Camera {
+    // unit vector the camera looks along
+    pub fn direction(&self) -> mq::Vec2 {
+        mq::Vec2::from_angle(self.angle)
+    }
+    // from the center of the screen to its right edge
+    pub fn plane(&self) -> mq::Vec2 {
+        self.direction().perp() * (self.fov / 2.0).tan()
+    }
+    // screen_x goes from -1.0 (left edge) to 1.0 (right edge)
+    pub fn ray_direction(&self, screen_x: f32) -> mq::Vec2 {
+        self.direction() + self.plane() * screen_x
+    }
+    // distance along direction, what the walls are scaled by
+    pub fn depth(&self, world_pos: mq::Vec2) -> f32 {
+        (world_pos - self.pos).dot(self.direction())
+    }
+    // pixels the view moves down by when looking up, in a view width pixels wide
+    pub fn shear(&self, width: u32) -> f32 {
+        self.angle_vertical.tan() * self.focal_length(width)
+    }
+    // row of the horizon in a view width x height pixels
+    pub fn floor_level(&self, width: u32, height: u32) -> f32 {
+        height as f32 / 2.0 + self.shear(width)
+    }
+    // row of a point z pixels above the floor and depth in front of the camera
+    pub fn project_y(&self, depth: f32, z: f32, width: u32, height: u32) -> f32 {
+        self.floor_level(width, height) + self.focal_length(width) * (self.height - z) / depth
+    }
+    // inverse of project_y for a horizontal plane z pixels above the floor, the
+    // depth seen at row_y, or None if the row is on the other side of the horizon
+    pub fn row_depth(&self, row_y: f32, z: f32, width: u32, height: u32) -> Option<f32> {
+        let depth = self.focal_length(width) * (self.height - z)
+            / (row_y - self.floor_level(width, height));
+        (depth > 0.0).then_some(depth)
+    }
+    // distance from the camera to the screen, in pixels of a view width pixels wide
+    pub fn focal_length(&self, width: u32) -> f32 {
+        width as f32 / 2.0 / (self.fov / 2.0).tan()
+    }
+    pub fn vertical_fov(&self, width: u32, height: u32) -> f32 {
+        2.0 * (height as f32 / 2.0 / self.focal_length(width)).atan()
+    }
+    // the rays for the first num_rays columns of a view width columns wide
+    pub fn cast_rays(&self, map: &Map, num_rays: u32, width: u32) -> Vec<(Ray, Option<RayHit>)> {
+        (0..num_rays)
+            .map(|i| self.cast_ray(map, i, width))
+            .collect()
+    }
+    // the ray through the center of column i of a view width columns wide
+    pub fn cast_ray(&self, map: &Map, i: u32, width: u32) -> (Ray, Option<RayHit>) {
+        let direction = self.ray_direction(column_to_screen_x(i, width));
+        let ray = Ray::new(self.pos, direction);
+        ray.cast_ray(map)
+    }
+}
+
+// the center of column i, from -1.0 to 1.0
+pub fn column_to_screen_x(i: u32, width: u32) -> f32 {
+    (2.0 * i as f32 + 1.0) / width as f32 - 1.0
+}
+
+pub struct Textures {
+    pub wall: Atlas,
+    pub floor: Atlas,
+    pub sprite: Atlas,
+    pub door: Atlas,
+    pub sky: Atlas,
+    pub door_texture: u8,
+    pub jamb_texture: u8,
+}
+impl Textures {
+    // the textures embedded in the binary
+    pub fn load() -> Self {
+        let embedded = |descriptor: &str, bytes: &[u8]| {
+            let image = mq::Image::from_file_with_format(bytes, Some(mq::ImageFormat::Png));
+            Atlas::parse(descriptor, image).expect("embedded atlases are valid")
+        };
+        Self::new(
+            embedded(
+                include_str!("../resources/walls.atlas"),
+                include_bytes!("../resources/WolfensteinTextures.png"),
+            ),
+            embedded(
+                include_str!("../resources/floors.atlas"),
+                include_bytes!("../resources/FloorTextures.png"),
+            ),
+            embedded(
+                include_str!("../resources/sprites.atlas"),
+                include_bytes!("../resources/SpriteTextures.png"),
+            ),
+            embedded(
+                include_str!("../resources/doors.atlas"),
+                include_bytes!("../resources/DoorTextures.png"),
+            ),
+            embedded(
+                include_str!("../resources/skies.atlas"),
+                include_bytes!("../resources/SkyTextures.png"),
+            ),
+        )
+        .expect("embedded door atlas has a door and a jamb")
+    }
+    // walls.atlas, floors.atlas, sprites.atlas, doors.atlas and skies.atlas in dir
+    // any that are missing use the embedded textures instead
+    pub fn load_dir(dir: &str) -> Result<Self, String> {
+        let embedded = Self::load();
+        let load = |name: &str, default: Atlas| {
+            let path = std::path::Path::new(dir).join(name);
+            if !path.exists() {
+                return Ok(default);
+            }
+            let path = path.to_string_lossy();
+            Atlas::load(&path).map_err(|err| format!("{}: {}", path, err))
+        };
+        Self::new(
+            load("walls.atlas", embedded.wall)?,
+            load("floors.atlas", embedded.floor)?,
+            load("sprites.atlas", embedded.sprite)?,
+            load("doors.atlas", embedded.door)?,
+            load("skies.atlas", embedded.sky)?,
+        )
+    }
+    fn new(
+        wall: Atlas,
+        floor: Atlas,
+        sprite: Atlas,
+        door: Atlas,
+        sky: Atlas,
+    ) -> Result<Self, String> {
+        let door_texture = door.id("door").ok_or("door atlas has no `door` texture")?;
+        let jamb_texture = door.id("jamb").ok_or("door atlas has no `jamb` texture")?;
+        Ok(Self {
+            wall,
+            floor,
+            sprite,
+            door,
+            sky,
+            door_texture,
+            jamb_texture,
+        })
+    }
+}
+
+// draws the 3D view into output_image without touching the window
+// the render resolution is the size of output_image, one ray per column
+// only the first num_columns columns are drawn (for the "loading" animation)
+pub fn render_frame(
+    output_image: &mut mq::Image,
+    map: &Map,
+    camera: &Camera,
+    textures: &Textures,
+    num_columns: u32,
+) -> Vec<(Ray, Option<RayHit>)> {
+    render_frame_threaded(
+        output_image,
+        map,
+        camera,
+        textures,
+        num_columns,
+        worker_threads(),
+    )
+}
+
+// same as render_frame, with the columns split between num_threads threads
+// every column is drawn on its own, so the image doesn't depend on num_threads
+pub fn render_frame_threaded(
+    output_image: &mut mq::Image,
+    map: &Map,
+    camera: &Camera,
+    textures: &Textures,
+    num_columns: u32,
+    num_threads: usize,
+) -> Vec<(Ray, Option<RayHit>)> {
+    // no threads on the web
+    let num_threads = if cfg!(target_arch = "wasm32") {
+        1
+    } else {
+        num_threads
+    };
+
+    let width = output_image.width();
+    let height = output_image.height();
+    // there is nowhere to draw columns past the right edge
+    let num_columns = num_columns.min(width as u32);
+    let frame = Frame::new(map, camera, textures, (width, height), num_columns);
+
+    // column major, so every thread gets a contiguous slice
+    let mut columns = vec![[0u8; 4]; num_columns as usize * height];
+    let columns_per_thread = (num_columns as usize).div_ceil(num_threads.max(1)).max(1);
+
+    let ray_touches = if num_threads <= 1 {
+        frame.draw_columns(0, &mut columns)
+    } else {
+        let frame = &frame;
+        std::thread::scope(|scope| {
+            let workers: Vec<_> = columns
+                .chunks_mut((columns_per_thread * height).max(1))
+                .enumerate()
+                .map(|(i, chunk)| {
+                    let first_x = (i * columns_per_thread) as u32;
+                    scope.spawn(move || frame.draw_columns(first_x, chunk))
+                })
+                .collect();
+            workers
+                .into_iter()
+                .flat_map(|worker| worker.join().expect("render thread panicked"))
+                .collect()
+        })
+    };
+
+    let image_data = output_image.get_image_data_mut();
+    for (x, column) in columns.chunks(height.max(1)).enumerate() {
+        for (y, pixel) in column.iter().enumerate() {
+            image_data[y * width + x] = *pixel;
+        }
+    }
+
+    ray_touches
+}
+
+// one per core on native builds
+pub fn worker_threads() -> usize {
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        std::thread::available_parallelism().map_or(1, |n| n.get())
+    }
+    #[cfg(target_arch = "wasm32")]
+    {
+        1
+    }
+}
+
+const RESOLUTION_LEVELS: u32 = 16; // steps between no and full resolution
+const MIN_RESOLUTION_LEVEL: u32 = 4;
+const RESOLUTION_SAMPLE_FRAMES: u32 = 15;
+// only scale back up when frames take less than this much of the budget
+const RESOLUTION_HEADROOM: f32 = 0.7;
+
+// scales the render resolution down while frames take longer than the budget
+// and back up once there is room again
+pub struct DynamicResolution {
+    max_width: u32,
+    max_height: u32,
+    budget: f32, // seconds
+    level: u32,
+    frame_time_sum: f32,
+    frames: u32,
+}
+impl DynamicResolution {
+    pub fn new(max_width: u32, max_height: u32, budget: f32) -> Self {
+        Self {
+            max_width,
+            max_height,
+            budget,
+            level: RESOLUTION_LEVELS,
+            frame_time_sum: 0.0,
+            frames: 0,
+        }
+    }
+    // frame_time is how long the last frame took to render, in seconds
+    pub fn update(&mut self, frame_time: f32) {
+        self.frame_time_sum += frame_time;
+        self.frames += 1;
+        if self.frames < RESOLUTION_SAMPLE_FRAMES {
+            return;
+        }
+
+        let average = self.frame_time_sum / self.frames as f32;
+        self.frame_time_sum = 0.0;
+        self.frames = 0;
+
+        if average > self.budget {
+            self.level = (self.level - 1).max(MIN_RESOLUTION_LEVEL);
+        } else if average < self.budget * RESOLUTION_HEADROOM {
+            self.level = (self.level + 1).min(RESOLUTION_LEVELS);
+        }
+    }
+    pub fn resolution(&self) -> (u32, u32) {
+        (
+            (self.max_width * self.level / RESOLUTION_LEVELS).max(1),
+            (self.max_height * self.level / RESOLUTION_LEVELS).max(1),
+        )
+    }
+}
+
+// mq::Image::export_png would flip the image for OpenGL and panics if the
+// file can't be written, so this encodes it directly
+pub fn save_png(image: &mq::Image, path: &str) -> Result<(), String> {
+    image::save_buffer_with_format(
+        path,
+        &image.bytes,
+        image.width() as u32,
+        image.height() as u32,
+        image::ColorType::Rgba8,
+        image::ImageFormat::Png,
+    )
+    .map_err(|err| format!("{}: {}", path, err))
+}
+
+// everything that is the same for every column of a frame
+struct Frame<'a> {
+    map: &'a Map,
+    camera: &'a Camera,
+    textures: &'a Textures,
+    width: u32,
+    height: usize,
+    rows: Vec<Row<'a>>,
+    sprites: Vec<ProjectedSprite>,
+    fog: FogTable,
+}
+impl<'a> Frame<'a> {
+    fn new(
+        map: &'a Map,
+        camera: &'a Camera,
+        textures: &'a Textures,
+        (width, height): (usize, usize),
+        num_columns: u32,
+    ) -> Self {
+        Self {
+            map,
+            camera,
+            textures,
+            width: width as u32,
+            height,
+            rows: floor_and_ceiling_rows(camera, (width, height), map, textures),
+            sprites: project_sprites(camera, (width, height), num_columns, map, textures),
+            fog: FogTable::new(map.fog),
+        }
+    }
+    fn draw_columns(&self, first_x: u32, pixels: &mut [[u8; 4]]) -> Vec<(Ray, Option<RayHit>)> {
+        pixels
+            .chunks_mut(self.height)
+            .enumerate()
+            .map(|(i, column)| self.draw_column(first_x + i as u32, column))
+            .collect()
+    }
+    // returns the wall that stopped the ray
+    fn draw_column(&self, x: u32, column: &mut [[u8; 4]]) -> (Ray, Option<RayHit>) {
+        let direction = self.camera.ray_direction(column_to_screen_x(x, self.width));
+        let (ray, mut ray_hits) =
+            Ray::new(self.camera.pos, direction).cast_ray_through(self.map, self.camera.height);
+
+        floor_and_ceiling(column, &self.rows, self.camera.pos, direction, &self.fog);
+
+        // front to back, every wall is only drawn above the ones in front of it
+        let mut clip_top = self.height as i32 - 1;
+        let mut occluders = Vec::with_capacity(ray_hits.len());
+        for ray_hit in ray_hits.iter() {
+            clip_top = self.draw_wall(column, clip_top, ray_hit, direction);
+            occluders.push((self.camera.depth(ray_hit.pos), clip_top));
+        }
+
+        draw_sprites(
+            column,
+            x,
+            &occluders,
+            &self.sprites,
+            &self.textures.sprite,
+            &self.fog,
+        );
+
+        (ray, ray_hits.pop())
+    }
+    // draws a wall in a single column, only the rows above clip_top (anything
+    // below it is a nearer wall), returns the new clip_top
+    // the texture repeats every tile of height, starting from the floor
+    fn draw_wall(
+        &self,
+        column: &mut [[u8; 4]],
+        clip_top: i32,
+        ray_hit: &RayHit,
+        direction: mq::Vec2,
+    ) -> i32 {
+        let (camera, map, textures, fog) = (self.camera, self.map, self.textures, &self.fog);
+        let (width, view_height) = (self.width, column.len() as u32);
+        let z = camera.depth(ray_hit.pos);
+        let height = map.wall_height(ray_hit.wall_type);
+
+        // the bottom tile of the wall is y0 to y1, the ones above it are h higher
+        let h = camera.focal_length(width) * TILE_SIZE as f32 / z;
+        let y0 = camera.project_y(z, TILE_SIZE as f32, width, view_height);
+        let y1 = y0 + h;
+        let wall_top = camera
+            .project_y(z, height * TILE_SIZE as f32, width, view_height)
+            .round() as i32;
+
+        let (atlas, id) = if ray_hit.wall_type == DOOR {
+            (&textures.door, textures.door_texture)
+        } else if ray_hit.door_jamb {
+            (&textures.door, textures.jamb_texture)
+        } else {
+            let tile_type = map.tile_type(ray_hit.wall_type);
+            (&textures.wall, tile_type.texture(ray_hit.face))
+        };
+
+        let (texture_x0, texture_y0) = atlas.origin(id);
+        let texture_column = ((ray_hit.wall_coord * atlas.tile_width as f32).round() as u32)
+            .min(atlas.tile_width - 1);
+        let texture = TextureColumn::new(
+            &atlas.image,
+            texture_x0 + texture_column,
+            texture_y0,
+            atlas.tile_height,
+        );
+        let lut = fog.lut(ray_hit.world_distance, !ray_hit.x_move);
+
+        let mut tile_bottom = y1.round() as i32;
+        for i in 0..height.ceil() as u32 {
+            let tile_top = (y0 - h * i as f32).round() as i32;
+            if tile_bottom <= tile_top {
+                break;
+            }
+
+            // the last tile is cut off at the top of the wall
+            let draw_y0 = tile_top.max(wall_top).clamp(0, column.len() as i32 - 1);
+            let draw_y1 = tile_bottom.min(clip_top).clamp(0, column.len() as i32 - 1);
+
+            if draw_y0 < draw_y1 {
+                let h_ratio = atlas.tile_height as f32 / (tile_bottom - tile_top) as f32;
+                let texture_y = to_fixed((draw_y0 - tile_top) as f32 * h_ratio);
+                blit::textured_span(
+                    column,
+                    draw_y0 as usize..draw_y1 as usize,
+                    &texture,
+                    texture_y,
+                    to_fixed(h_ratio),
+                    lut,
+                    None,
+                );
+            }
+            tile_bottom = tile_top;
+        }
+
+        // the top is only visible on walls lower than the camera
+        let top_z = height * TILE_SIZE as f32;
+        if ray_hit.wall_type == DOOR || camera.height <= top_z {
+            return clip_top.min(wall_top);
+        }
+        let exit_z = camera.depth(ray_hit.exit_pos);
+        let top_far = camera.project_y(exit_z, top_z, width, view_height).round() as i32;
+
+        let tile_type = map.tile_type(ray_hit.wall_type);
+        let (texture_x0, texture_y0) = textures.wall.origin(tile_type.north);
+        let texture_w = textures.wall.tile_width;
+        let texture_h = textures.wall.tile_height;
+        let texels = textures.wall.image.get_image_data();
+        let direction_length = direction.length();
+
+        let draw_y0 = top_far.clamp(0, column.len() as i32);
+        let draw_y1 = wall_top.min(clip_top).clamp(0, column.len() as i32);
+        for y in draw_y0..draw_y1 {
+            // same as the floor, on a plane top_z above it
+            let Some(z) = camera.row_depth(y as f32 + 0.5, top_z, width, view_height) else {
+                continue;
+            };
+            let map_pos = (camera.pos + direction * z) / TILE_SIZE as f32;
+            let tile_pos = map_pos - map_pos.floor();
+            let texture_x = ((tile_pos.x * texture_w as f32) as u32).min(texture_w - 1);
+            let texture_y = ((tile_pos.y * texture_h as f32) as u32).min(texture_h - 1);
+
+            let texel = texels[((texture_y0 + texture_y) as usize) * textures.wall.image.width()
+                + (texture_x0 + texture_x) as usize];
+            let lut = fog.lut(z * direction_length, false);
+            column[y as usize] = lut.apply(texel);
+        }
+
+        clip_top.min(wall_top).min(top_far)
+    }
+}
+
+// a row of the floor or ceiling
+// every pixel in a row is the same (perpendicular) distance away
+enum Row<'a> {
+    Flat {
+        color: [u8; 4],
+        z: f32,
+    },
+    Textured {
+        atlas: &'a Atlas,
+        origin: (u32, u32),
+        z: f32,
+    },
+    // the same row of the sky texture, which column depends on the ray's angle
+    Sky {
+        atlas: &'a Atlas,
+        origin: (u32, u32),
+        texture_y: u32,
+    },
+}
+
+fn floor_and_ceiling_rows<'a>(
+    camera: &Camera,
+    (width, height): (usize, usize),
+    map: &Map,
+    textures: &'a Textures,
+) -> Vec<Row<'a>> {
+    let (width, height) = (width as u32, height as u32);
+    let floor_level = camera.floor_level(width, height);
+    let focal_length = camera.focal_length(width);
+    (0..height)
+        .map(|y| {
+            let row_center = y as f32 + 0.5;
+            // distance from the camera to the floor, or else the ceiling (a tile high)
+            let (surface, flat_color, z) = match camera.row_depth(row_center, 0.0, width, height) {
+                Some(z) => (map.floor, GROUND_COLOR, z),
+                None => {
+                    if let (Surface::Flat, Some(id)) = (map.ceiling, map.sky) {
+                        let elevation = (floor_level - row_center) / focal_length;
+                        return sky_row(&textures.sky, id, elevation);
+                    }
+                    let z = camera.row_depth(row_center, TILE_SIZE as f32, width, height);
+                    (map.ceiling, BACKGROUND_COLOR, z.unwrap_or(f32::INFINITY))
+                }
+            };
+
+            let (atlas, id) = match surface {
+                Surface::Flat => {
+                    return Row::Flat {
+                        color: flat_color.into(),
+                        z,
+                    }
+                }
+                Surface::Wall(id) => (&textures.wall, id),
+                Surface::Floor(id) => (&textures.floor, id),
+            };
+
+            Row::Textured {
+                atlas,
+                origin: atlas.origin(id),
+                z,
+            }
+        })
+        .collect()
+}
+
+// the sky is a cylinder around the camera, infinitely far away, so it only
+// depends on the direction: the texture wraps once around the player with its
+// bottom row on the horizon, elevation is the tangent of the angle above it
+fn sky_row(atlas: &Atlas, id: u8, elevation: f32) -> Row<'_> {
+    // square texels, a tile_width around cylinder is tile_width / 2pi in radius
+    let radius = atlas.tile_width as f32 / std::f32::consts::TAU;
+    let texture_y = (atlas.tile_height as f32 - elevation * radius).max(0.0) as u32;
+    Row::Sky {
+        atlas,
+        origin: atlas.origin(id),
+        texture_y: texture_y.min(atlas.tile_height - 1),
+    }
+}
+
+// casts the floor and ceiling of one column, direction is the column's ray
+fn floor_and_ceiling(
+    column: &mut [[u8; 4]],
+    rows: &[Row],
+    camera_pos: mq::Vec2,
+    direction: mq::Vec2,
+    fog: &FogTable,
+) {
+    let direction_length = direction.length();
+    // turning scrolls the sky, a full turn is the whole texture
+    let sky_u = (direction.y.atan2(direction.x) / std::f32::consts::TAU).rem_euclid(1.0);
+
+    for (pixel, row) in column.iter_mut().zip(rows) {
+        let (atlas, (texture_x0, texture_y0), z) = match *row {
+            Row::Flat { color, z } => {
+                *pixel = fog.lut(z * direction_length, false).apply(color);
+                continue;
+            }
+            Row::Sky {
+                atlas,
+                origin: (texture_x0, texture_y0),
+                texture_y,
+            } => {
+                let texture_x =
+                    ((sky_u * atlas.tile_width as f32) as u32).min(atlas.tile_width - 1);
+                *pixel = atlas.image.get_image_data()[((texture_y0 + texture_y) as usize)
+                    * atlas.image.width()
+                    + (texture_x0 + texture_x) as usize];
+                continue;
+            }
+            Row::Textured { atlas, origin, z } => (atlas, origin, z),
+        };
+        let texture_w = atlas.tile_width;
+        let texture_h = atlas.tile_height;
+
+        let world_pos = camera_pos + direction * z;
+
+        let map_pos = world_pos / TILE_SIZE as f32;
+        let tile_pos = map_pos - map_pos.floor();
+        let texture_x = ((tile_pos.x * texture_w as f32) as u32).min(texture_w - 1);
+        let texture_y = ((tile_pos.y * texture_h as f32) as u32).min(texture_h - 1);
+
+        let texel = atlas.image.get_image_data()[((texture_y0 + texture_y) as usize)
+            * atlas.image.width()
+            + (texture_x0 + texture_x) as usize];
+        let lut = fog.lut(z * direction_length, false);
+        *pixel = lut.apply(texel);
+    }
+}
+
+// a billboard's place on the screen
+struct ProjectedSprite {
+    z: f32,
+    x0: f32,
+    w: f32,
+    y0: f32,
+    h: f32,
+    draw_x: std::ops::Range<u32>,
+    draw_y: std::ops::Range<usize>,
+    texture_origin: (u32, u32),
+    distance: f32, // from the camera, for the fog
+}
+
+// sorted back to front, sprites behind the camera are left out
+fn project_sprites(
+    camera: &Camera,
+    (width, height): (usize, usize),
+    num_columns: u32,
+    map: &Map,
+    textures: &Textures,
+) -> Vec<ProjectedSprite> {
+    let focal_length = camera.focal_length(width as u32);
+
+    let mut sprites: Vec<(f32, &Sprite)> = map
+        .sprites
+        .iter()
+        .map(|sprite| (sprite.pos.distance(camera.pos / TILE_SIZE as f32), sprite))
+        .collect();
+    sprites.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+    sprites
+        .into_iter()
+        .filter_map(|(_, sprite)| {
+            let relative_pos = sprite.pos * TILE_SIZE as f32 - camera.pos;
+
+            // distance along the view direction, same as z for the walls
+            let z = camera.depth(sprite.pos * TILE_SIZE as f32);
+            if z < 1.0 {
+                return None;
+            }
+
+            // inverse of Camera::ray_direction
+            let screen_x = relative_pos.dot(camera.plane()) / camera.plane().length_squared() / z;
+            let center_x = (screen_x + 1.0) / 2.0 * width as f32;
+            let w = focal_length * TILE_SIZE as f32 / z;
+            let h = w;
+            let x0 = center_x - w / 2.0;
+            let y0 = camera.project_y(z, TILE_SIZE as f32, width as u32, height as u32);
+
+            let draw_x0 = (x0.round() as i32).clamp(0, num_columns as i32) as u32;
+            let draw_x1 = ((x0 + w).round() as i32).clamp(0, num_columns as i32) as u32;
+            let draw_y0 = (y0.round() as i32).clamp(0, height as i32) as usize;
+            let draw_y1 = ((y0 + h).round() as i32).clamp(0, height as i32) as usize;
+
+            Some(ProjectedSprite {
+                z,
+                x0,
+                w,
+                y0,
+                h,
+                draw_x: draw_x0..draw_x1,
+                draw_y: draw_y0..draw_y1,
+                texture_origin: textures.sprite.origin(sprite.texture),
+                distance: relative_pos.length(),
+            })
+        })
+        .collect()
+}
+
+// billboards in column x, drawn back to front and clipped against the walls
+// occluders are the walls' depths and clip_tops, front to back
+fn draw_sprites(
+    column: &mut [[u8; 4]],
+    x: u32,
+    occluders: &[(f32, i32)],
+    sprites: &[ProjectedSprite],
+    sprite_atlas: &Atlas,
+    fog: &FogTable,
+) {
+    let texture_w = sprite_atlas.tile_width as f32;
+    let texture_h = sprite_atlas.tile_height as f32;
+
+    for sprite in sprites {
+        if !sprite.draw_x.contains(&x) {
+            continue;
+        }
+        // sprites stand on the floor, so the walls in front of them only
+        // hide the rows below their clip_top
+        let draw_y_end = occluders
+            .iter()
+            .take_while(|(z, _)| *z < sprite.z)
+            .last()
+            .map_or(sprite.draw_y.end, |(_, clip_top)| {
+                sprite.draw_y.end.min((*clip_top).max(0) as usize)
+            });
+        if draw_y_end <= sprite.draw_y.start {
+            continue;
+        }
+        let (texture_x0, texture_y0) = sprite.texture_origin;
+        let texture_x = ((x as f32 + 0.5 - sprite.x0) / sprite.w * texture_w).min(texture_w - 1.0);
+        let texture = TextureColumn::new(
+            &sprite_atlas.image,
+            texture_x0 + texture_x as u32,
+            texture_y0,
+            sprite_atlas.tile_height,
+        );
+
+        let texture_y = (sprite.draw_y.start as f32 + 0.5 - sprite.y0) / sprite.h * texture_h;
+        blit::textured_span(
+            column,
+            sprite.draw_y.start..draw_y_end,
+            &texture,
+            to_fixed(texture_y),
+            to_fixed(texture_h / sprite.h),
+            fog.lut(sprite.distance, false),
+            Some(SPRITE_TRANSPARENT_COLOR.into()),
+        );
+    }
+}
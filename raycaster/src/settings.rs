@@ -0,0 +1,157 @@
+use std::fmt;
+use std::ops::RangeInclusive;
+
+use crate::FOV;
+
+/*
+    Settings file format:
+
+    # comments start with a '#'
+    fov: 90                   # horizontal, in degrees
+    mouse_sensitivity: 0.001  # radians per pixel
+    move_speed: 100           # pixels per second (a tile is 64 pixels)
+    turn_speed: 3             # radians per second, for the arrow keys
+    player_radius: 16         # pixels, how close the player gets to walls
+    max_pitch: 80             # degrees, how far the player can look up or down
+
+    Every key is optional, missing ones keep their default.
+*/
+
+pub const FOV_RANGE: RangeInclusive<f32> = 60.0..=120.0; // degrees
+pub const MOUSE_SENSITIVITY_RANGE: RangeInclusive<f32> = 0.0001..=0.01;
+pub const MOVE_SPEED_RANGE: RangeInclusive<f32> = 25.0..=400.0;
+pub const TURN_SPEED_RANGE: RangeInclusive<f32> = 0.5..=10.0;
+// the player has to fit through a corridor one tile wide
+pub const PLAYER_RADIUS_RANGE: RangeInclusive<f32> = 4.0..=28.0;
+// the view is sheared instead of rotated, so the horizon leaves the screen long
+// before 90 degrees
+pub const MAX_PITCH_RANGE: RangeInclusive<f32> = 0.0..=80.0;
+
+#[derive(Debug)]
+pub enum SettingsError {
+    Io(std::io::Error),
+    Syntax { line: usize },
+    UnknownKey { line: usize, key: String },
+    InvalidValue { line: usize, key: String },
+}
+impl fmt::Display for SettingsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SettingsError::Io(err) => write!(f, "could not read settings: {}", err),
+            SettingsError::Syntax { line } => write!(f, "line {}: expected `key: value`", line),
+            SettingsError::UnknownKey { line, key } => {
+                write!(f, "line {}: unknown key `{}`", line, key)
+            }
+            SettingsError::InvalidValue { line, key } => {
+                write!(f, "line {}: invalid value for `{}`", line, key)
+            }
+        }
+    }
+}
+impl std::error::Error for SettingsError {}
+impl From<std::io::Error> for SettingsError {
+    fn from(err: std::io::Error) -> Self {
+        SettingsError::Io(err)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Settings {
+    pub fov: f32, // in degrees
+    pub mouse_sensitivity: f32,
+    pub move_speed: f32,
+    pub turn_speed: f32,
+    pub player_radius: f32,
+    pub max_pitch: f32, // in degrees
+}
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            fov: FOV.to_degrees(),
+            mouse_sensitivity: 0.001,
+            move_speed: 100.0,
+            turn_speed: 3.0,
+            player_radius: 16.0,
+            max_pitch: 80.0,
+        }
+    }
+}
+impl Settings {
+    // a missing file is the default settings, and so is no filesystem at all
+    // (the web build)
+    pub fn load(path: &str) -> Result<Self, SettingsError> {
+        match std::fs::read_to_string(path) {
+            Ok(source) => Self::parse(&source),
+            Err(err)
+                if matches!(
+                    err.kind(),
+                    std::io::ErrorKind::NotFound | std::io::ErrorKind::Unsupported
+                ) =>
+            {
+                Ok(Self::default())
+            }
+            Err(err) => Err(err.into()),
+        }
+    }
+    pub fn parse(source: &str) -> Result<Self, SettingsError> {
+        let mut settings = Self::default();
+
+        for (i, line) in source.lines().enumerate() {
+            let line_number = i + 1;
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let (key, value) = line
+                .split_once(':')
+                .ok_or(SettingsError::Syntax { line: line_number })?;
+            let (key, value) = (key.trim(), value.trim());
+            let (setting, range) = match key {
+                "fov" => (&mut settings.fov, FOV_RANGE),
+                "mouse_sensitivity" => (&mut settings.mouse_sensitivity, MOUSE_SENSITIVITY_RANGE),
+                "move_speed" => (&mut settings.move_speed, MOVE_SPEED_RANGE),
+                "turn_speed" => (&mut settings.turn_speed, TURN_SPEED_RANGE),
+                "player_radius" => (&mut settings.player_radius, PLAYER_RADIUS_RANGE),
+                "max_pitch" => (&mut settings.max_pitch, MAX_PITCH_RANGE),
+                _ => {
+                    return Err(SettingsError::UnknownKey {
+                        line: line_number,
+                        key: key.to_owned(),
+                    })
+                }
+            };
+            *setting = value
+                .parse::<f32>()
+                .ok()
+                .filter(|value| range.contains(value))
+                .ok_or_else(|| SettingsError::InvalidValue {
+                    line: line_number,
+                    key: key.to_owned(),
+                })?;
+        }
+
+        Ok(settings)
+    }
+    pub fn save(&self, path: &str) -> Result<(), SettingsError> {
+        std::fs::write(path, self.to_string())?;
+        Ok(())
+    }
+    pub fn fov_radians(&self) -> f32 {
+        self.fov.to_radians()
+    }
+    pub fn max_pitch_radians(&self) -> f32 {
+        self.max_pitch.to_radians()
+    }
+}
+// in the settings file format
+impl fmt::Display for Settings {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "fov: {}", self.fov)?;
+        writeln!(f, "mouse_sensitivity: {}", self.mouse_sensitivity)?;
+        writeln!(f, "move_speed: {}", self.move_speed)?;
+        writeln!(f, "turn_speed: {}", self.turn_speed)?;
+        writeln!(f, "player_radius: {}", self.player_radius)?;
+        writeln!(f, "max_pitch: {}", self.max_pitch)
+    }
+}
@@ -0,0 +1,91 @@
+use macroquad::prelude as mq;
+
+use raycaster::atlas::{Atlas, AtlasError};
+
+// room for a 2x2 grid of 4x4 textures
+fn image() -> mq::Image {
+    mq::Image::gen_image_color(8, 8, mq::WHITE)
+}
+
+#[test]
+fn textures_are_numbered_in_order() {
+    let atlas = Atlas::parse(
+        "image: test.png\ntile_size: 4 4\ngrid: 2 2\ntextures:\na\nb\nc\n",
+        image(),
+    )
+    .unwrap();
+    assert_eq!(atlas.len(), 3);
+    assert_eq!(atlas.id("c"), Some(3));
+    assert_eq!(atlas.origin(2), (4, 0));
+    assert_eq!(atlas.origin(3), (0, 4));
+    assert!(!atlas.contains(0) && !atlas.contains(4));
+}
+
+#[test]
+fn missing_image() {
+    assert!(matches!(
+        Atlas::parse("tile_size: 4 4\ntextures:\na\n", image()),
+        Err(AtlasError::MissingImage)
+    ));
+}
+
+#[test]
+fn duplicate_name() {
+    assert!(matches!(
+        Atlas::parse(
+            "image: test.png\ntile_size: 4 4\ntextures:\na\nb\na\n",
+            image()
+        ),
+        Err(AtlasError::DuplicateName { line: 6, .. })
+    ));
+}
+
+#[test]
+fn too_many_textures() {
+    assert!(matches!(
+        Atlas::parse(
+            "image: test.png\ntile_size: 4 4\ngrid: 1 2\ntextures:\na\nb\nc\n",
+            image()
+        ),
+        Err(AtlasError::TooManyTextures { capacity: 2 })
+    ));
+}
+
+#[test]
+fn image_too_small() {
+    assert!(matches!(
+        Atlas::parse(
+            "image: test.png\ntile_size: 8 8\ngrid: 2 1\ntextures:\na\nb\n",
+            image()
+        ),
+        Err(AtlasError::ImageTooSmall)
+    ));
+}
+
+#[test]
+fn huge_sizes_do_not_overflow() {
+    // 70000 * 70000 doesn't fit in a u32
+    assert!(matches!(
+        Atlas::parse(
+            "image: test.png\ntile_size: 70000 70000\ngrid: 70000 70000\ntextures:\na\n",
+            image()
+        ),
+        Err(AtlasError::ImageTooSmall)
+    ));
+}
+
+#[test]
+fn files_that_are_not_images() {
+    let dir = std::env::temp_dir().join(format!("raycaster-atlas-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("test.png"), "not a png").unwrap();
+    std::fs::write(
+        dir.join("test.atlas"),
+        "image: test.png\ntile_size: 4 4\ntextures:\na\n",
+    )
+    .unwrap();
+
+    let result = Atlas::load(dir.join("test.atlas").to_str().unwrap());
+    std::fs::remove_dir_all(&dir).unwrap();
+    assert!(matches!(result, Err(AtlasError::Decode(_))));
+}
@@ -0,0 +1,96 @@
+use macroquad::prelude as mq;
+
+use proptest::prelude::*;
+
+use raycaster::collision::move_circle;
+use raycaster::map::Map;
+use raycaster::TILE_SIZE;
+
+const RADIUS: f32 = 16.0;
+const EPSILON: f32 = 1e-3;
+
+// 5x5 room with a pillar in the middle
+const ROOM: &str = "
+spawn: 1.5 1.5 0
+tiles:
+1 1 1 1 1
+1 0 0 0 1
+1 0 1 0 1
+1 0 0 0 1
+1 1 1 1 1
+";
+
+fn tiles(x: f32, y: f32) -> mq::Vec2 {
+    mq::Vec2::new(x, y) * TILE_SIZE as f32
+}
+
+// distance from pos to the closest solid tile (or the outside of the map)
+fn distance_to_walls(map: &Map, pos: mq::Vec2) -> f32 {
+    let tile_size = TILE_SIZE as f32;
+    let mut closest = f32::INFINITY;
+    for y in -1..=map.height as i32 {
+        for x in -1..=map.width as i32 {
+            if map.is_solid(x, y) {
+                let tile_min = mq::Vec2::new(x as f32, y as f32) * tile_size;
+                let nearest = pos.clamp(tile_min, tile_min + tile_size);
+                closest = closest.min(pos.distance(nearest));
+            }
+        }
+    }
+    closest
+}
+
+#[test]
+fn open_space_moves_freely() {
+    let map = Map::parse(ROOM).unwrap();
+    let pos = move_circle(&map, tiles(1.5, 1.5), RADIUS, mq::Vec2::new(20.0, 0.0));
+    assert!(pos.distance(tiles(1.5, 1.5) + mq::Vec2::new(20.0, 0.0)) < EPSILON);
+}
+
+#[test]
+fn stops_a_radius_away_from_walls() {
+    let map = Map::parse(ROOM).unwrap();
+    let pos = move_circle(&map, tiles(1.5, 1.5), RADIUS, mq::Vec2::new(-100.0, 0.0));
+    assert!((pos.x - (TILE_SIZE as f32 + RADIUS)).abs() < EPSILON);
+    assert!((pos.y - tiles(1.5, 1.5).y).abs() < EPSILON);
+}
+
+#[test]
+fn slides_along_walls() {
+    let map = Map::parse(ROOM).unwrap();
+    // into the top wall at 45 degrees, only the part along the wall is kept
+    let pos = move_circle(&map, tiles(1.5, 1.5), RADIUS, mq::Vec2::new(30.0, -30.0));
+    assert!((pos.x - (tiles(1.5, 1.5).x + 30.0)).abs() < EPSILON);
+    assert!((pos.y - (TILE_SIZE as f32 + RADIUS)).abs() < EPSILON);
+}
+
+#[test]
+fn fast_moves_do_not_tunnel() {
+    // a wall one tile thick between two rooms
+    let map = Map::parse("spawn: 0.5 0.5 0\ntiles:\n0 1 0\n").unwrap();
+    let pos = move_circle(&map, tiles(0.5, 0.5), RADIUS, mq::Vec2::new(10_000.0, 0.0));
+    assert!(pos.x < TILE_SIZE as f32);
+}
+
+#[test]
+fn cannot_leave_through_a_gap_in_the_border() {
+    let map = Map::parse("spawn: 1.5 0.5 0\ntiles:\n1 0 1\n1 0 1\n").unwrap();
+    let pos = move_circle(&map, tiles(1.5, 1.5), RADIUS, mq::Vec2::new(0.0, 500.0));
+    assert!((pos.y - (2.0 * TILE_SIZE as f32 - RADIUS)).abs() < EPSILON);
+}
+
+proptest! {
+    #[test]
+    fn never_gets_closer_than_the_radius(
+        start in (1.3..1.7_f32, 1.3..1.7_f32),
+        moves in prop::collection::vec((-200.0..200.0_f32, -200.0..200.0_f32), 1..10),
+        radius in 4.0..28.0_f32,
+    ) {
+        let map = Map::parse(ROOM).unwrap();
+        let mut pos = tiles(start.0, start.1);
+        for (x, y) in moves {
+            pos = move_circle(&map, pos, radius, mq::Vec2::new(x, y));
+            prop_assert!(distance_to_walls(&map, pos) > radius - EPSILON * 10.0);
+        }
+    }
+}
@@ -0,0 +1,67 @@
+use macroquad::prelude as mq;
+
+use raycaster::explored::Explored;
+use raycaster::map::Map;
+use raycaster::ray::Ray;
+use raycaster::{TILE_SIZE, VIEW_DISTANCE};
+
+// a corridor with a wall in the middle of it
+const CORRIDOR: &str = "
+spawn: 0.5 0.5 0
+tiles:
+0 0 1 0 0
+0 0 0 0 0
+";
+
+fn reveal(map: &Map, explored: &mut Explored, pos_in_tiles: (f32, f32), direction: (f32, f32)) {
+    let pos = mq::Vec2::new(pos_in_tiles.0, pos_in_tiles.1) * TILE_SIZE as f32;
+    let (ray, hit) = Ray::new(pos, mq::Vec2::new(direction.0, direction.1)).cast_ray(map);
+    explored.reveal(&ray, hit.as_ref());
+}
+
+#[test]
+fn nothing_is_seen_at_first() {
+    let explored = Explored::new(5, 2);
+    assert!((0..2).all(|y| (0..5).all(|x| !explored.is_seen(x, y))));
+    assert!(!explored.is_seen(-1, 0));
+    assert!(!explored.is_seen(5, 0));
+}
+
+#[test]
+fn rays_stop_revealing_at_walls() {
+    let map = Map::parse(CORRIDOR).unwrap();
+    let mut explored = Explored::new(map.width, map.height);
+
+    reveal(&map, &mut explored, (0.5, 0.5), (1.0, 0.0));
+    assert!(explored.is_seen(0, 0));
+    assert!(explored.is_seen(1, 0));
+    // the wall that was hit, but not what is behind it
+    assert!(explored.is_seen(2, 0));
+    assert!(!explored.is_seen(3, 0));
+    assert!(!explored.is_seen(0, 1));
+}
+
+#[test]
+fn rays_that_leave_the_map_reveal_everything_on_the_way() {
+    let map = Map::parse(CORRIDOR).unwrap();
+    let mut explored = Explored::new(map.width, map.height);
+
+    reveal(&map, &mut explored, (0.5, 1.5), (1.0, 0.0));
+    assert!((0..5).all(|x| explored.is_seen(x, 1)));
+    assert!(!explored.is_seen(2, 0));
+
+    // diagonally through the corner of a tile
+    reveal(&map, &mut explored, (3.5, 1.5), (1.0, -1.0));
+    assert!(explored.is_seen(4, 0));
+}
+
+#[test]
+fn rays_that_hit_nothing_stop_at_the_view_distance() {
+    let map = Map::parse("spawn: 0.5 0.5 0\ntiles:\n0 0 0 0 0 0 0 0 0 0 0 0\n").unwrap();
+    let mut explored = Explored::new(map.width, map.height);
+
+    reveal(&map, &mut explored, (0.5, 0.5), (1.0, 0.0));
+    let view_distance = (VIEW_DISTANCE / TILE_SIZE as f32) as i32;
+    assert!((0..=view_distance).all(|x| explored.is_seen(x, 0)));
+    assert!(!explored.is_seen(view_distance + 1, 0));
+}
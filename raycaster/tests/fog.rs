@@ -0,0 +1,48 @@
+use raycaster::fog::{Falloff, Fog};
+
+const EPSILON: f32 = 1e-4;
+
+fn fog(falloff: Falloff) -> Fog {
+    Fog {
+        falloff,
+        start: 100.0,
+        end: 300.0,
+        ..Fog::default()
+    }
+}
+
+#[test]
+fn no_fog_before_the_start() {
+    for falloff in [
+        Falloff::Linear,
+        Falloff::Exponential,
+        Falloff::ExponentialSquared,
+    ] {
+        assert_eq!(fog(falloff).amount(0.0), 0.0);
+        assert_eq!(fog(falloff).amount(100.0), 0.0);
+    }
+}
+
+#[test]
+fn falloffs() {
+    let linear = fog(Falloff::Linear);
+    assert!((linear.amount(200.0) - 0.5).abs() < EPSILON);
+    assert_eq!(linear.amount(300.0), 1.0);
+    assert_eq!(linear.amount(f32::INFINITY), 1.0);
+
+    // both exponential falloffs are 95% fog at the end
+    let exponential = fog(Falloff::Exponential);
+    let squared = fog(Falloff::ExponentialSquared);
+    assert!((exponential.amount(300.0) - 0.95).abs() < 0.01);
+    assert!((squared.amount(300.0) - 0.95).abs() < 0.01);
+    // exp fades in faster at first, exp2 catches up later
+    assert!(exponential.amount(150.0) > linear.amount(150.0));
+    assert!(squared.amount(120.0) < exponential.amount(120.0));
+    assert!(exponential.amount(1000.0) < 1.0 + EPSILON);
+
+    // never decreases with distance
+    for falloff in [linear, exponential, squared] {
+        let amounts: Vec<f32> = (0..100).map(|i| falloff.amount(i as f32 * 5.0)).collect();
+        assert!(amounts.windows(2).all(|pair| pair[0] <= pair[1]));
+    }
+}
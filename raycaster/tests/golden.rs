@@ -0,0 +1,125 @@
+// Golden image tests for the headless renderer
+// To update an image after an intended change to the renderer:
+// cargo run -- [level.map] --render tests/golden/<name>.png [--resolution widthxheight] [--camera x,y,angle,angle_vertical]
+
+use macroquad::prelude as mq;
+
+use std::process::Command;
+
+// libm's tan, atan and exp differ a little between platforms, which moves a
+// few texels and fog levels, so small differences are allowed
+const CHANNEL_TOLERANCE: u8 = 8;
+const DIFFERENT_PIXELS_ALLOWED: f32 = 0.001; // fraction of the image
+
+fn load_png(path: &str) -> mq::Image {
+    let bytes = std::fs::read(path).unwrap_or_else(|err| panic!("{}: {}", path, err));
+    mq::Image::from_file_with_format(&bytes, Some(mq::ImageFormat::Png))
+}
+
+fn assert_matches_golden(name: &str, args: &[&str]) {
+    let output_path = format!("{}/{}.png", env!("CARGO_TARGET_TMPDIR"), name);
+    let status = Command::new(env!("CARGO_BIN_EXE_raycaster"))
+        .args(args)
+        .args(["--render", &output_path])
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    let golden = load_png(&format!(
+        "{}/tests/golden/{}.png",
+        env!("CARGO_MANIFEST_DIR"),
+        name
+    ));
+    let output = load_png(&output_path);
+    assert_eq!(
+        (output.width(), output.height()),
+        (golden.width(), golden.height())
+    );
+
+    let different_pixels = output
+        .get_image_data()
+        .iter()
+        .zip(golden.get_image_data())
+        .filter(|(a, b)| {
+            a.iter()
+                .zip(b.iter())
+                .any(|(a, b)| a.abs_diff(*b) > CHANNEL_TOLERANCE)
+        })
+        .count();
+    let allowed = (golden.get_image_data().len() as f32 * DIFFERENT_PIXELS_ALLOWED) as usize;
+    assert!(
+        different_pixels <= allowed,
+        "{} differs from the golden image in {} pixels, see {}",
+        name,
+        different_pixels,
+        output_path
+    );
+}
+
+#[test]
+fn spawn() {
+    assert_matches_golden("spawn", &[]);
+}
+
+#[test]
+fn door_and_sprites() {
+    assert_matches_golden("door_and_sprites", &["--camera", "2.5,6.5,-70,-15"]);
+}
+
+#[test]
+fn large_map() {
+    assert_matches_golden(
+        "large_map",
+        &["resources/maps/large.map", "--camera", "8.5,8.5,200,0"],
+    );
+}
+
+#[test]
+fn half_resolution() {
+    assert_matches_golden(
+        "half_resolution",
+        &["--resolution", "256x256", "--camera", "2.5,6.5,-70,-15"],
+    );
+}
+
+#[test]
+fn widescreen() {
+    assert_matches_golden(
+        "widescreen",
+        &["--resolution", "640x360", "--camera", "2.5,6.5,-70,-15"],
+    );
+}
+
+#[test]
+fn variable_height_walls() {
+    assert_matches_golden(
+        "variable_height_walls",
+        &["resources/maps/ledges.map", "--camera", "1.5,2.5,10,-10"],
+    );
+}
+
+#[test]
+fn crouching_camera() {
+    assert_matches_golden(
+        "crouching_camera",
+        &["resources/maps/ledges.map", "--camera", "1.5,2.5,10,0,0.2"],
+    );
+}
+
+#[test]
+fn sky() {
+    assert_matches_golden(
+        "sky",
+        &["resources/maps/ledges.map", "--camera", "5.5,6.5,-120,25"],
+    );
+}
+
+#[test]
+fn unwritable_render_path_is_an_error() {
+    let output = Command::new(env!("CARGO_BIN_EXE_raycaster"))
+        .args(["--render", "this/directory/does/not/exist/frame.png"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("failed to save render"));
+}
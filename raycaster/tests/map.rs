@@ -0,0 +1,238 @@
+use raycaster::fog::{Falloff, Fog};
+use raycaster::map::{Map, MapError, DOOR};
+use raycaster::render::Textures;
+use raycaster::TILE_SIZE;
+
+const DEFAULT: &str = include_str!("../resources/maps/default.map");
+
+#[test]
+fn saved_levels_load_back() {
+    let mut map = Map::parse(DEFAULT).unwrap();
+    map.name = "Default: the first level".to_owned();
+    let loaded = Map::parse(&map.to_string()).unwrap();
+
+    assert_eq!(loaded.name, map.name);
+    assert_eq!((loaded.width, loaded.height), (map.width, map.height));
+    assert_eq!(loaded.tiles, map.tiles);
+    assert_eq!(loaded.tile_types, map.tile_types);
+    assert_eq!(loaded.spawn, map.spawn);
+    assert_eq!(loaded.spawn_angle, map.spawn_angle);
+    assert_eq!(loaded.sprites.len(), map.sprites.len());
+    assert_eq!(loaded.doors.len(), map.doors.len());
+
+    let path = std::env::temp_dir().join(format!("raycaster-level-{}.map", std::process::id()));
+    let path = path.to_str().unwrap();
+    map.save(path).unwrap();
+    let saved = Map::load(path).unwrap();
+    assert_eq!(
+        (saved.name, saved.tiles),
+        (map.name.clone(), map.tiles.clone())
+    );
+    std::fs::remove_file(path).unwrap();
+
+    // the rest of the name would load as a comment or another line
+    for name in ["Level #2", "Two\nlines"] {
+        map.name = name.to_owned();
+        assert!(matches!(map.save(path), Err(MapError::InvalidName)));
+    }
+}
+
+#[test]
+fn painted_doors_are_found() {
+    let mut map = Map::parse("spawn: 0.5 0.5 0\ntiles:\n0 0 0\n1 0 1\n").unwrap();
+    assert!(map.doors.is_empty());
+
+    map.set_tile(1, 1, DOOR);
+    assert_eq!(map.doors.len(), 1);
+    assert!(map.doors[0].x_aligned);
+    assert!(map.is_solid(1, 1));
+
+    map.set_tile(1, 1, 0);
+    assert!(map.doors.is_empty());
+
+    // new ids use the texture with the same number
+    map.set_tile(2, 0, 3);
+    assert_eq!(map.tile_type(3).west, 3);
+}
+
+#[test]
+fn resize_keeps_the_top_left_corner() {
+    let mut map = Map::parse(DEFAULT).unwrap();
+    let tiles = map.tiles.clone();
+
+    map.resize(10, 9);
+    assert_eq!((map.width, map.height), (10, 9));
+    assert_eq!(map.tile(7, 7), tiles[7 * 8 + 7]);
+    assert_eq!(map.tile(9, 8), 0);
+
+    map.resize(5, 5);
+    assert_eq!(map.tile(3, 3), tiles[3 * 8 + 3]);
+    // the sprites at 6.5 1.5 and 2.5 5.5 are outside now
+    assert_eq!(map.sprites.len(), 2);
+    // and so is the door at 4 5
+    assert!(map.doors.is_empty());
+}
+
+#[test]
+fn levels_that_would_not_load_are_not_saved() {
+    let mut map = Map::parse(DEFAULT).unwrap();
+    let path = "this/file/is/never/written.map";
+
+    map.set_tile(4, 4, 1);
+    assert!(matches!(map.save(path), Err(MapError::SpawnInWall)));
+
+    map.set_tile(4, 4, 0);
+    map.resize(3, 3);
+    assert!(matches!(map.save(path), Err(MapError::SpawnOutOfBounds)));
+}
+
+#[test]
+fn outside_of_the_map_is_the_border() {
+    let map = Map::parse("spawn: 0.5 0.5 0\ntiles:\n0 1\n").unwrap();
+    assert_eq!(map.get(1, 0), Some(1));
+    assert_eq!(map.get(-1, 0), None);
+    assert_eq!(map.get(0, 1), None);
+    assert_eq!(map.tile(2, 0), 0);
+    assert!(map.is_solid(-1, 0));
+    assert!(map.is_solid(0, 1));
+    assert!(!map.is_solid(0, 0));
+
+    let map = Map::parse("spawn: 0.5 0.5 0\nborder: 2\ntiles:\n0 1\n").unwrap();
+    assert_eq!(map.tile(-5, 3), 2);
+    assert_eq!(map.tile_type(2).north, 2);
+    assert_eq!(Map::parse(&map.to_string()).unwrap().border, 2);
+
+    assert!(matches!(
+        Map::parse("spawn: 0.5 0.5 0\nborder: D\ntiles:\n0 1\n"),
+        Err(MapError::InvalidValue { line: 2, .. })
+    ));
+}
+
+#[test]
+fn walls_have_heights() {
+    let map = Map::parse(include_str!("../resources/maps/ledges.map")).unwrap();
+    assert_eq!(map.wall_height(0), 0.0);
+    assert_eq!(map.wall_height(1), 1.0);
+    assert_eq!(map.wall_height(4), 0.25);
+    assert_eq!(map.wall_height(DOOR), 1.0);
+    assert_eq!(map.max_wall_height(), 3.0);
+    assert_eq!(
+        Map::parse(&map.to_string()).unwrap().tile_types,
+        map.tile_types
+    );
+
+    for height in ["0", "-1", "9", "tall"] {
+        let source = format!(
+            "spawn: 0.5 0.5 0\ntile: 2 1 1 1 1 {}\ntiles:\n0 2\n",
+            height
+        );
+        assert!(matches!(
+            Map::parse(&source),
+            Err(MapError::InvalidValue { line: 2, .. })
+        ));
+    }
+}
+
+#[test]
+fn levels_can_have_a_sky() {
+    let textures = Textures::load();
+    let map = Map::parse(include_str!("../resources/maps/ledges.map")).unwrap();
+    assert_eq!(map.sky, Some(1));
+    assert_eq!(Map::parse(&map.to_string()).unwrap().sky, Some(1));
+    assert!(map.check_textures(&textures).is_ok());
+    assert_eq!(Map::parse(DEFAULT).unwrap().sky, None);
+
+    let map = Map::parse("spawn: 0.5 0.5 0\nsky: 9\ntiles:\n0 1\n").unwrap();
+    assert!(matches!(
+        map.check_textures(&textures),
+        Err(MapError::UnknownTexture {
+            atlas: "sky",
+            texture: 9
+        })
+    ));
+    assert!(matches!(
+        Map::parse("spawn: 0.5 0.5 0\nsky: 0\ntiles:\n0 1\n"),
+        Err(MapError::InvalidValue { line: 2, .. })
+    ));
+}
+
+#[test]
+fn levels_have_their_own_fog() {
+    assert_eq!(Map::parse(DEFAULT).unwrap().fog, Fog::default());
+
+    let map = Map::parse(include_str!("../resources/maps/ledges.map")).unwrap();
+    assert_eq!(map.fog.falloff, Falloff::ExponentialSquared);
+    assert_eq!(
+        (map.fog.start, map.fog.end),
+        (3.0 * TILE_SIZE as f32, 12.0 * TILE_SIZE as f32)
+    );
+    assert_eq!(
+        map.fog.color,
+        macroquad::prelude::Color::from_rgba(158, 209, 247, 255)
+    );
+    assert_eq!(Map::parse(&map.to_string()).unwrap().fog, map.fog);
+
+    for fog in [
+        "fog: fast 1 2",
+        "fog: linear 2 1",
+        "fog: exp -1 2",
+        "fog: linear nan 2",
+        "fog: exp 0 inf",
+        "fog_color: 1 2",
+        "fog_color: 0 0 256",
+    ] {
+        let source = format!("spawn: 0.5 0.5 0\n{}\ntiles:\n0 1\n", fog);
+        assert!(matches!(
+            Map::parse(&source),
+            Err(MapError::InvalidValue { line: 2, .. })
+        ));
+    }
+}
+
+#[test]
+fn rows_must_be_the_same_length() {
+    assert!(matches!(
+        Map::parse("spawn: 0.5 0.5 0\ntiles:\n0 0 0\n0 0\n"),
+        Err(MapError::RaggedRow {
+            line: 4,
+            expected: 3,
+            found: 2
+        })
+    ));
+}
+
+#[test]
+fn tiles_must_be_numbers_or_doors() {
+    assert!(matches!(
+        Map::parse("spawn: 0.5 0.5 0\ntiles:\n0 0\n0 X\n"),
+        Err(MapError::UnknownTile { line: 4, .. })
+    ));
+}
+
+#[test]
+fn levels_need_a_spawn() {
+    assert!(matches!(
+        Map::parse("name: No spawn\ntiles:\n0 0\n"),
+        Err(MapError::MissingSpawn)
+    ));
+    // and one that is inside the map
+    for spawn in ["nan nan 0", "inf 0.5 0", "0.5 0.5 inf"] {
+        let source = format!("spawn: {}\ntiles:\n0 0\n", spawn);
+        assert!(Map::parse(&source).is_err(), "{}", spawn);
+    }
+    assert!(matches!(
+        Map::parse("spawn: -0.5 0.5 0\ntiles:\n0 0\n"),
+        Err(MapError::SpawnOutOfBounds)
+    ));
+}
+
+#[test]
+fn sprites_must_have_a_position() {
+    for sprite in ["nan nan 1", "1.5 inf 1", "1.5 1.5", "1.5 1.5 0"] {
+        let source = format!("spawn: 0.5 0.5 0\nsprite: {}\ntiles:\n0 0\n", sprite);
+        assert!(matches!(
+            Map::parse(&source),
+            Err(MapError::InvalidValue { line: 2, .. })
+        ));
+    }
+}
@@ -0,0 +1,246 @@
+use macroquad::prelude as mq;
+
+use proptest::prelude::*;
+
+use raycaster::map::{Map, DOOR};
+use raycaster::ray::{Ray, RayHit};
+use raycaster::TILE_SIZE;
+
+const EPSILON: f32 = 1e-3;
+
+// 8x8 room with a wall all the way around it
+const ROOM: &str = "
+spawn: 4.5 4.5 0
+tiles:
+1 1 1 1 1 1 1 1
+1 0 0 0 0 0 0 1
+1 0 0 0 0 0 0 1
+1 0 0 0 0 0 0 1
+1 0 0 0 0 0 0 1
+1 0 0 0 0 0 0 1
+1 0 0 0 0 0 0 1
+1 1 1 1 1 1 1 1
+";
+
+// a single pillar with nothing around it
+const OPEN: &str = "
+spawn: 0.5 0.5 0
+tiles:
+0 0 0
+0 2 0
+0 0 0
+";
+
+fn cast(map: &Map, pos_in_tiles: (f32, f32), direction: (f32, f32)) -> Option<RayHit> {
+    let pos = mq::Vec2::new(pos_in_tiles.0, pos_in_tiles.1) * TILE_SIZE as f32;
+    let ray = Ray::new(pos, mq::Vec2::new(direction.0, direction.1));
+    ray.cast_ray(map).1
+}
+
+fn assert_close(a: f32, b: f32) {
+    assert!((a - b).abs() < EPSILON, "{} != {}", a, b);
+}
+
+#[test]
+fn axis_aligned_rays() {
+    let map = Map::parse(ROOM).unwrap();
+
+    // (direction, hit tile, x_move, distance in tiles)
+    let cases = [
+        ((1.0, 0.0), (7, 4), true, 2.5),
+        ((-1.0, 0.0), (0, 4), true, 3.5),
+        ((0.0, 1.0), (4, 7), false, 2.5),
+        ((0.0, -1.0), (4, 0), false, 3.5),
+    ];
+    for (direction, tile, x_move, distance) in cases {
+        let hit = cast(&map, (4.5, 4.5), direction).expect("the room is closed");
+        assert_eq!(hit.tile, tile);
+        assert_eq!(hit.x_move, x_move);
+        assert_close(hit.world_distance, distance * TILE_SIZE as f32);
+        assert_close(hit.wall_coord, 0.5);
+    }
+}
+
+#[test]
+fn ray_starting_on_tile_boundary() {
+    let map = Map::parse(ROOM).unwrap();
+
+    let hit = cast(&map, (4.0, 4.5), (-1.0, 0.0)).unwrap();
+    assert_eq!(hit.tile, (0, 4));
+    assert_close(hit.world_distance, 3.0 * TILE_SIZE as f32);
+
+    let hit = cast(&map, (4.0, 4.5), (1.0, 0.0)).unwrap();
+    assert_eq!(hit.tile, (7, 4));
+    assert_close(hit.world_distance, 3.0 * TILE_SIZE as f32);
+
+    // exactly on a corner
+    let hit = cast(&map, (4.0, 4.0), (1.0, 1.0)).unwrap();
+    assert_close(hit.world_distance, 3.0 * 2.0_f32.sqrt() * TILE_SIZE as f32);
+}
+
+#[test]
+fn ray_against_wall_it_starts_next_to() {
+    let map = Map::parse(ROOM).unwrap();
+
+    let hit = cast(&map, (1.0, 4.5), (-1.0, 0.0)).unwrap();
+    assert_eq!(hit.tile, (0, 4));
+    assert_close(hit.world_distance, 0.0);
+}
+
+#[test]
+fn rays_leaving_the_map() {
+    let map = Map::parse(OPEN).unwrap();
+
+    assert!(cast(&map, (0.5, 0.5), (-1.0, 0.0)).is_none());
+    assert!(cast(&map, (0.5, 0.5), (0.0, -1.0)).is_none());
+    assert!(cast(&map, (0.5, 0.5), (-1.0, 0.3)).is_none());
+    assert!(cast(&map, (2.5, 2.5), (1.0, 1.0)).is_none());
+
+    let hit = cast(&map, (0.5, 0.5), (1.0, 1.0)).unwrap();
+    assert_eq!(hit.tile, (1, 1));
+    assert_eq!(hit.wall_type, 2);
+}
+
+#[test]
+fn ray_starting_outside_the_map() {
+    let map = Map::parse(OPEN).unwrap();
+
+    assert!(cast(&map, (-2.5, 1.5), (-1.0, 0.0)).is_none());
+
+    let hit = cast(&map, (-2.5, 1.5), (1.0, 0.0)).unwrap();
+    assert_eq!(hit.tile, (1, 1));
+    assert_close(hit.world_distance, 3.5 * TILE_SIZE as f32);
+}
+
+#[test]
+fn rays_hit_the_border() {
+    let map = Map::parse(&format!("border: 3\n{}", OPEN)).unwrap();
+
+    let hit = cast(&map, (0.5, 0.5), (-1.0, 0.0)).unwrap();
+    assert_eq!(hit.tile, (-1, 0));
+    assert_eq!(hit.wall_type, 3);
+    assert_close(hit.world_distance, 0.5 * TILE_SIZE as f32);
+
+    let hit = cast(&map, (2.5, 2.5), (0.0, 1.0)).unwrap();
+    assert_eq!(hit.tile, (2, 3));
+}
+
+#[test]
+fn rays_continue_past_low_walls() {
+    // a ledge, a full wall and a pillar two tiles tall
+    let map = Map::parse(
+        "spawn: 0.5 0.5 0\ntile: 2 1 1 1 1 0.5\ntile: 3 1 1 1 1 2\ntiles:\n0 2 0 1 0 3 0 1\n",
+    )
+    .unwrap();
+    let pos = mq::Vec2::new(0.5, 0.5) * TILE_SIZE as f32;
+    let ray = Ray::new(pos, mq::Vec2::new(1.0, 0.0));
+
+    // the first hit is still the ledge
+    assert_eq!(ray.cast_ray(&map).1.unwrap().tile, (1, 0));
+
+    let (_, hits) = ray.cast_ray_through(&map, TILE_SIZE as f32 / 2.0);
+    let tiles = hits.iter().map(|hit| hit.tile).collect::<Vec<_>>();
+    assert_eq!(tiles, [(1, 0), (3, 0), (5, 0)]);
+    assert_close(hits[0].exit_pos.x, 2.0 * TILE_SIZE as f32);
+}
+
+#[test]
+fn doors_missing_from_doors_are_closed() {
+    let mut map = Map::parse("spawn: 0.5 1.5 0\ntiles:\n1 1 1\n0 0 0\n1 1 1\n").unwrap();
+    // changed without set_tile, so doors doesn't know about it
+    map.tiles[4] = DOOR;
+    assert!(map.doors.is_empty());
+
+    let hit = cast(&map, (0.5, 1.5), (1.0, 0.0)).unwrap();
+    assert_eq!(hit.wall_type, DOOR);
+    assert_close(hit.world_distance, TILE_SIZE as f32);
+}
+
+#[test]
+fn zero_direction_does_not_hit() {
+    let map = Map::parse(ROOM).unwrap();
+
+    assert!(cast(&map, (4.5, 4.5), (0.0, 0.0)).is_none());
+}
+
+// random maps with a closed border and random walls inside
+fn closed_map() -> impl Strategy<Value = Map> {
+    (3..16_usize, 3..16_usize)
+        .prop_flat_map(|(width, height)| {
+            (
+                Just((width, height)),
+                prop::collection::vec(prop_oneof![3 => Just(0_u8), 1 => 1..=3_u8], width * height),
+            )
+        })
+        .prop_map(|((width, height), mut tiles)| {
+            for y in 0..height {
+                for x in 0..width {
+                    if x == 0 || y == 0 || x == width - 1 || y == height - 1 {
+                        tiles[y * width + x] = 1;
+                    }
+                }
+            }
+            // keep the spawn tile empty
+            tiles[width + 1] = 0;
+
+            let rows = tiles
+                .chunks(width)
+                .map(|row| {
+                    row.iter()
+                        .map(|tile| tile.to_string())
+                        .collect::<Vec<String>>()
+                        .join(" ")
+                })
+                .collect::<Vec<String>>()
+                .join("\n");
+            Map::parse(&format!("spawn: 1.5 1.5 0\ntiles:\n{}", rows)).unwrap()
+        })
+}
+
+proptest! {
+    #[test]
+    fn hits_are_consistent(
+        map in closed_map(),
+        offset in (0.01..0.99_f32, 0.01..0.99_f32),
+        angle in 0.0..std::f32::consts::TAU,
+    ) {
+        let start = mq::Vec2::new(1.0 + offset.0, 1.0 + offset.1) * TILE_SIZE as f32;
+        let direction = mq::Vec2::new(angle.cos(), angle.sin());
+
+        let (_, hit) = Ray::new(start, direction).cast_ray(&map);
+        // the border is closed so every ray has to hit something
+        let hit = hit.unwrap();
+
+        // the reported tile is a wall
+        prop_assert!(map.get(hit.tile.0, hit.tile.1).is_some());
+        prop_assert_ne!(map.tile(hit.tile.0, hit.tile.1), 0);
+        prop_assert_eq!(map.tile(hit.tile.0, hit.tile.1), hit.wall_type);
+
+        // pos is world_distance along the ray
+        let expected_pos = start + direction * hit.world_distance;
+        prop_assert!(hit.pos.distance(expected_pos) < EPSILON * TILE_SIZE as f32);
+
+        // pos is on the face of the reported tile
+        let map_pos = hit.pos / TILE_SIZE as f32;
+        let tile = mq::Vec2::new(hit.tile.0 as f32, hit.tile.1 as f32);
+        prop_assert!(map_pos.x > tile.x - EPSILON && map_pos.x < tile.x + 1.0 + EPSILON);
+        prop_assert!(map_pos.y > tile.y - EPSILON && map_pos.y < tile.y + 1.0 + EPSILON);
+        let (across, along) = if hit.x_move {
+            (map_pos.x, map_pos.y)
+        } else {
+            (map_pos.y, map_pos.x)
+        };
+        prop_assert!((across - across.round()).abs() < EPSILON);
+
+        // wall_coord is where on the face the ray hit
+        prop_assert!((0.0..=1.0).contains(&hit.wall_coord));
+        prop_assert!((along - along.floor() - hit.wall_coord).abs() < EPSILON);
+
+        // nothing solid between the start and the hit
+        let steps = (hit.world_distance / 4.0) as usize;
+        for i in 0..steps {
+            let pos = (start + direction * (i as f32 * 4.0)) / TILE_SIZE as f32;
+            prop_assert_eq!(map.tile(pos.x as i32, pos.y as i32), 0);
+        }
+    }
+}
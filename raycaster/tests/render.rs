@@ -0,0 +1,224 @@
+use macroquad::prelude as mq;
+
+const EPSILON: f32 = 1e-4;
+
+use raycaster::map::Map;
+use raycaster::render::{self, Camera, DynamicResolution, Textures};
+use raycaster::{EYE_HEIGHT, FOV, RENDER_HEIGHT, RENDER_WIDTH, TILE_SIZE};
+
+fn camera(x: f32, y: f32, angle: f32, angle_vertical: f32) -> Camera {
+    Camera {
+        pos: mq::Vec2::new(x, y) * TILE_SIZE as f32,
+        angle: angle.to_radians(),
+        angle_vertical: angle_vertical.to_radians(),
+        fov: FOV,
+        height: EYE_HEIGHT,
+    }
+}
+
+fn render(
+    map: &Map,
+    camera: &Camera,
+    textures: &Textures,
+    columns: u32,
+    threads: usize,
+) -> Vec<u8> {
+    let mut image =
+        mq::Image::gen_image_color(RENDER_WIDTH as u16, RENDER_HEIGHT as u16, mq::BLACK);
+    render::render_frame_threaded(&mut image, map, camera, textures, columns, threads);
+    image.bytes
+}
+
+#[test]
+fn threads_match_serial() {
+    let map = Map::parse(include_str!("../resources/maps/default.map")).unwrap();
+    let textures = Textures::load();
+    let cameras = [
+        camera(4.5, 4.5, 0.0, 0.0),
+        camera(2.5, 6.5, -70.0, -15.0),
+        camera(1.5, 1.5, 45.0, 20.0),
+    ];
+
+    for camera in &cameras {
+        // the full view and a partly drawn "loading" view
+        for columns in [RENDER_WIDTH, 301] {
+            let serial = render(&map, camera, &textures, columns, 1);
+            for threads in [2, 3, 8] {
+                assert!(
+                    serial == render(&map, camera, &textures, columns, threads),
+                    "{} threads differ from serial at {} columns",
+                    threads,
+                    columns
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn threads_return_rays_in_order() {
+    let map = Map::parse(include_str!("../resources/maps/default.map")).unwrap();
+    let textures = Textures::load();
+    let camera = camera(4.5, 4.5, 30.0, 0.0);
+
+    let mut image =
+        mq::Image::gen_image_color(RENDER_WIDTH as u16, RENDER_HEIGHT as u16, mq::BLACK);
+    let ray_touches = render::render_frame_threaded(&mut image, &map, &camera, &textures, 300, 7);
+    let expected = camera.cast_rays(&map, 300, RENDER_WIDTH);
+
+    assert_eq!(ray_touches.len(), expected.len());
+    for ((ray, hit), (expected_ray, expected_hit)) in ray_touches.iter().zip(&expected) {
+        assert_eq!(ray.angle, expected_ray.angle);
+        assert_eq!(
+            hit.as_ref().map(|hit| hit.pos),
+            expected_hit.as_ref().map(|hit| hit.pos)
+        );
+    }
+}
+
+#[test]
+fn more_columns_than_the_image_is_wide() {
+    let map = Map::parse(include_str!("../resources/maps/default.map")).unwrap();
+    let textures = Textures::load();
+    let camera = camera(4.5, 4.5, 30.0, 0.0);
+
+    for threads in [1, 3] {
+        let mut image = mq::Image::gen_image_color(64, 64, mq::BLACK);
+        let ray_touches =
+            render::render_frame_threaded(&mut image, &map, &camera, &textures, 100, threads);
+        assert_eq!(ray_touches.len(), 64);
+    }
+}
+
+#[test]
+fn dynamic_resolution_scales_down_and_back_up() {
+    let budget = 1.0 / 120.0;
+    let mut resolution = DynamicResolution::new(640, 400, budget);
+    assert_eq!(resolution.resolution(), (640, 400));
+
+    let mut previous = resolution.resolution();
+    for _ in 0..1000 {
+        resolution.update(budget * 2.0);
+        let (width, height) = resolution.resolution();
+        assert!(width <= previous.0 && height <= previous.1);
+        previous = (width, height);
+    }
+    // bottoms out instead of going to nothing, keeping the aspect ratio
+    let (width, height) = resolution.resolution();
+    assert!(width > 0 && width < 640);
+    assert_eq!(width * 400, height * 640);
+
+    for _ in 0..1000 {
+        resolution.update(budget / 4.0);
+    }
+    assert_eq!(resolution.resolution(), (640, 400));
+}
+
+#[test]
+fn dynamic_resolution_holds_within_budget() {
+    let budget = 1.0 / 120.0;
+    let mut resolution = DynamicResolution::new(512, 512, budget);
+    for _ in 0..1000 {
+        resolution.update(budget * 0.9);
+    }
+    assert_eq!(resolution.resolution(), (512, 512));
+}
+
+#[test]
+fn edge_rays_land_at_half_fov() {
+    for fov in [60.0_f32, 90.0, 120.0] {
+        let fov = fov.to_radians();
+        for angle in [0.0, 30.0, 90.0, 135.0, 200.0, -70.0] {
+            let camera = Camera {
+                fov,
+                ..camera(4.5, 4.5, angle, 0.0)
+            };
+            let left = camera.direction().angle_between(camera.ray_direction(-1.0));
+            let right = camera.direction().angle_between(camera.ray_direction(1.0));
+            assert!((left + fov / 2.0).abs() < EPSILON, "left edge at {}", left);
+            assert!(
+                (right - fov / 2.0).abs() < EPSILON,
+                "right edge at {}",
+                right
+            );
+        }
+    }
+}
+
+#[test]
+fn columns_are_evenly_spaced_on_the_plane() {
+    let camera = camera(4.5, 4.5, 30.0, 0.0);
+    let width = 320;
+
+    // the outer columns are half a column in from the edges, on both sides
+    let first = render::column_to_screen_x(0, width);
+    let last = render::column_to_screen_x(width - 1, width);
+    assert!((first + 1.0 - 1.0 / width as f32).abs() < EPSILON);
+    assert!((last - 1.0 + 1.0 / width as f32).abs() < EPSILON);
+
+    let step = 2.0 / width as f32;
+    for i in 1..width {
+        let spacing =
+            render::column_to_screen_x(i, width) - render::column_to_screen_x(i - 1, width);
+        assert!((spacing - step).abs() < EPSILON);
+    }
+
+    // every ray is in front of the camera and inside the FOV
+    for i in 0..width {
+        let direction = camera.ray_direction(render::column_to_screen_x(i, width));
+        let angle = camera.direction().angle_between(direction);
+        assert!(angle.abs() < FOV / 2.0);
+        assert!((direction.dot(camera.direction()) - 1.0).abs() < EPSILON);
+    }
+}
+
+#[test]
+fn vertical_fov_follows_aspect_ratio() {
+    let camera = camera(4.5, 4.5, 0.0, 0.0);
+    assert!((camera.vertical_fov(512, 512) - FOV).abs() < EPSILON);
+    assert!((camera.vertical_fov(256, 256) - FOV).abs() < EPSILON);
+
+    let wide = camera.vertical_fov(640, 360);
+    assert!(wide < FOV);
+    let ratio = (wide / 2.0).tan() / (FOV / 2.0).tan();
+    assert!((ratio - 360.0 / 640.0).abs() < EPSILON);
+}
+
+#[test]
+fn depth_is_distance_along_the_view() {
+    let camera = camera(2.0, 2.0, 90.0, 0.0);
+    // a tile straight ahead and the same distance ahead but off to the side
+    let ahead = camera.pos + mq::Vec2::new(0.0, 3.0) * TILE_SIZE as f32;
+    let beside = camera.pos + mq::Vec2::new(2.0, 3.0) * TILE_SIZE as f32;
+    assert!((camera.depth(ahead) - 3.0 * TILE_SIZE as f32).abs() < 1e-3);
+    assert!((camera.depth(beside) - 3.0 * TILE_SIZE as f32).abs() < 1e-3);
+}
+
+#[test]
+fn looking_up_shears_every_row_equally() {
+    let level = camera(4.5, 4.5, 0.0, 0.0);
+    let up = camera(4.5, 4.5, 0.0, 30.0);
+    let shear = up.shear(RENDER_WIDTH);
+    assert!(shear > 0.0);
+    assert!(
+        (up.floor_level(RENDER_WIDTH, RENDER_HEIGHT) - RENDER_HEIGHT as f32 / 2.0 - shear).abs()
+            < 1e-3
+    );
+
+    for (depth, z) in [(64.0, 0.0), (100.0, 64.0), (300.0, 20.0), (1000.0, 128.0)] {
+        let y = level.project_y(depth, z, RENDER_WIDTH, RENDER_HEIGHT);
+        let y_up = up.project_y(depth, z, RENDER_WIDTH, RENDER_HEIGHT);
+        assert!((y_up - y - shear).abs() < 1e-3);
+        // row_depth finds the same depth back on a plane at that height
+        let found = up.row_depth(y_up, z, RENDER_WIDTH, RENDER_HEIGHT);
+        assert!((found.unwrap() - depth).abs() < 1e-2 * depth);
+    }
+    // rows above the horizon never see the floor
+    let horizon = up.floor_level(RENDER_WIDTH, RENDER_HEIGHT);
+    assert!(up
+        .row_depth(horizon - 1.0, 0.0, RENDER_WIDTH, RENDER_HEIGHT)
+        .is_none());
+    assert!(up
+        .row_depth(horizon - 1.0, TILE_SIZE as f32, RENDER_WIDTH, RENDER_HEIGHT)
+        .is_some());
+}
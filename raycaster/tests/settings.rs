@@ -0,0 +1,64 @@
+use raycaster::settings::{Settings, SettingsError};
+
+#[test]
+fn missing_keys_keep_defaults() {
+    let settings = Settings::parse("# only the fov\nfov: 75\n").unwrap();
+    assert_eq!(
+        settings,
+        Settings {
+            fov: 75.0,
+            ..Settings::default()
+        }
+    );
+    assert_eq!(Settings::parse("").unwrap(), Settings::default());
+}
+
+#[test]
+fn saved_settings_load_back() {
+    let settings = Settings {
+        fov: 104.5,
+        mouse_sensitivity: 0.0025,
+        move_speed: 180.0,
+        turn_speed: 4.25,
+        player_radius: 10.0,
+        max_pitch: 45.0,
+    };
+    assert_eq!(Settings::parse(&settings.to_string()).unwrap(), settings);
+
+    let path = std::env::temp_dir().join(format!("raycaster-settings-{}.cfg", std::process::id()));
+    let path = path.to_str().unwrap();
+    settings.save(path).unwrap();
+    assert_eq!(Settings::load(path).unwrap(), settings);
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn missing_file_is_default() {
+    let settings = Settings::load("this/file/does/not/exist.cfg").unwrap();
+    assert_eq!(settings, Settings::default());
+}
+
+#[test]
+fn invalid_settings_are_rejected() {
+    assert!(matches!(
+        Settings::parse("fov: 90\nfov: 10\n"),
+        Err(SettingsError::InvalidValue { line: 2, .. })
+    ));
+    assert!(matches!(
+        Settings::parse("move_speed: fast"),
+        Err(SettingsError::InvalidValue { line: 1, .. })
+    ));
+    // looking straight up would shear the view infinitely far
+    assert!(matches!(
+        Settings::parse("max_pitch: 90"),
+        Err(SettingsError::InvalidValue { line: 1, .. })
+    ));
+    assert!(matches!(
+        Settings::parse("volume: 11"),
+        Err(SettingsError::UnknownKey { line: 1, .. })
+    ));
+    assert!(matches!(
+        Settings::parse("fov 90"),
+        Err(SettingsError::Syntax { line: 1 })
+    ));
+}